@@ -1,5 +1,5 @@
 use baphomet::input::*;
-use baphomet::{Application, Engine, Rgba, Ticker};
+use baphomet::{Application, Engine, Rgba};
 use rand::prelude::*;
 use std::error::Error;
 use std::time::Duration;
@@ -9,20 +9,41 @@ fn main() -> Result<(), Box<dyn Error>> {
         builder.resizable().position_centered()
     })?;
 
-    let mut app = TestApp {
-        title_update: Ticker::new(Duration::from_millis(100)),
-        point_timer: Ticker::new(Duration::from_millis(250)),
-    };
+    let actions = engine.action_map_mut();
+    actions.bind_str("quit", "Escape")?;
+    actions.bind_str("toggle_vsync", "1")?;
+
+    engine
+        .scheduler()
+        .every(Duration::from_millis(100), |engine: &mut Engine| {
+            let vsync_label = if engine.vsync() { " (vsync)" } else { "" };
+            let _ = engine.window.set_title(&format!(
+                "TestApp | {:.2} fps{}",
+                engine.frame_counter.fps(),
+                vsync_label
+            ));
+        });
+
+    engine
+        .scheduler()
+        .every(Duration::from_millis(250), |engine: &mut Engine| {
+            engine.g2d.point(
+                (
+                    rand::rng().random_range(..engine.window.size().0) as f32,
+                    rand::rng().random_range(..engine.window.size().1) as f32,
+                ),
+                &rand_color(),
+            );
+        });
+
+    let mut app = TestApp;
 
     baphomet::run_app(&mut engine, &mut app);
 
     Ok(())
 }
 
-struct TestApp {
-    title_update: Ticker,
-    point_timer: Ticker,
-}
+struct TestApp;
 
 fn rand_color() -> Rgba {
     Rgba::new(
@@ -35,47 +56,16 @@ fn rand_color() -> Rgba {
 
 impl Application for TestApp {
     fn update(&mut self, engine: &mut Engine, _dt: f32) {
-        if self.title_update.tick() > 0 {
-            let vsync_label = if engine.vsync() { " (vsync)" } else { "" };
-            let _ = engine.window.set_title(&format!(
-                "TestApp | {:.2} fps{}",
-                engine.frame_counter.fps(),
-                vsync_label
-            ));
+        if engine.actions().pressed("quit") {
+            engine.shutdown();
         }
-    }
-
-    fn draw(&mut self, engine: &mut Engine) {
-        for _ in 0..self.point_timer.tick() {
-            engine.g2d.point(
-                (
-                    rand::rng().random_range(..engine.window.size().0) as f32,
-                    rand::rng().random_range(..engine.window.size().1) as f32,
-                ),
-                &rand_color(),
-            );
+        if engine.actions().pressed("toggle_vsync") {
+            let is_vsync = engine.vsync();
+            engine.set_vsync(!is_vsync);
         }
     }
 
-    fn key_event(
-        &mut self,
-        engine: &mut Engine,
-        keycode: Option<Keycode>,
-        _scancode: Option<Scancode>,
-        _mods: Mod,
-        action: KeyAction,
-    ) {
-        match keycode {
-            Some(Keycode::Escape) => engine.shutdown(),
-            Some(Keycode::_1) => {
-                if action == KeyAction::Press {
-                    let is_vsync = engine.vsync();
-                    engine.set_vsync(!is_vsync);
-                }
-            }
-            _ => (),
-        }
-    }
+    fn draw(&mut self, _engine: &mut Engine, _alpha: f32) {}
 
     fn mouse_button_event(
         &mut self,
@@ -83,6 +73,7 @@ impl Application for TestApp {
         x: f32,
         y: f32,
         button: MouseButton,
+        _clicks: u8,
         action: MouseAction,
     ) {
         if button == MouseButton::Left && action == MouseAction::Press {
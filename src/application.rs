@@ -1,11 +1,15 @@
 use crate::{
     Engine,
-    input::{KeyAction, MouseAction, MouseButton},
+    input::{GamepadAction, GamepadAxis, GamepadButton, KeyAction, MouseAction, MouseButton},
 };
+use std::path::{Path, PathBuf};
 
 pub trait Application {
+    // Called zero or more times per frame with a constant step, see Engine::set_fixed_step_rate
+    fn fixed_update(&mut self, _engine: &mut Engine, _step: f32) {}
     fn update(&mut self, engine: &mut Engine, dt: f32);
-    fn draw(&mut self, engine: &mut Engine);
+    // alpha is how far we are between the previous and the current fixed update, in [0, 1)
+    fn draw(&mut self, engine: &mut Engine, alpha: f32);
 
     fn key_event(
         &mut self,
@@ -22,7 +26,68 @@ pub trait Application {
         _x: f32,
         _y: f32,
         _button: MouseButton,
+        _clicks: u8,
         _action: MouseAction,
     ) {
     }
+    // dx/dy are relative to the last motion event, and keep coming in relative mouse mode
+    fn mouse_motion_event(&mut self, _engine: &mut Engine, _x: f32, _y: f32, _dx: f32, _dy: f32) {}
+    // Positive scroll_y is away from the user, regardless of "natural" scrolling settings
+    fn mouse_wheel_event(
+        &mut self,
+        _engine: &mut Engine,
+        _x: f32,
+        _y: f32,
+        _scroll_x: f32,
+        _scroll_y: f32,
+    ) {
+    }
+
+    // Committed text, only sent between Engine::start_text_input and stop_text_input
+    fn text_input_event(&mut self, _engine: &mut Engine, _text: &str) {}
+    // Text still being composed in an IME, start/length is the selection within it
+    fn text_editing_event(&mut self, _engine: &mut Engine, _text: &str, _start: i32, _length: i32) {
+    }
+
+    // A drop is grouped as begin, any number of files/text, complete
+    fn drop_begin_event(&mut self, _engine: &mut Engine) {}
+    fn file_dropped_event(&mut self, _engine: &mut Engine, _path: &Path, _x: f32, _y: f32) {}
+    fn text_dropped_event(&mut self, _engine: &mut Engine, _text: &str, _x: f32, _y: f32) {}
+    // All files from this drop, in the order they were dropped
+    fn drop_complete_event(&mut self, _engine: &mut Engine, _paths: &[PathBuf]) {}
+
+    fn gamepad_connected_event(&mut self, _engine: &mut Engine, _id: u32) {}
+    fn gamepad_disconnected_event(&mut self, _engine: &mut Engine, _id: u32) {}
+    fn gamepad_button_event(
+        &mut self,
+        _engine: &mut Engine,
+        _id: u32,
+        _button: GamepadButton,
+        _action: GamepadAction,
+    ) {
+    }
+    // Only sent when the value changes after applying dead zones, see Engine::set_gamepad_dead_zones
+    fn gamepad_axis_event(
+        &mut self,
+        _engine: &mut Engine,
+        _id: u32,
+        _axis: GamepadAxis,
+        _value: f32,
+    ) {
+    }
+
+    // Return false to keep running, e.g. to ask the user about unsaved work first
+    fn on_quit_requested(&mut self, _engine: &mut Engine) -> bool {
+        true
+    }
+
+    // Logical size, in the same units as mouse coordinates
+    fn window_resize_event(&mut self, _engine: &mut Engine, _width: u32, _height: u32) {}
+    // Size of the drawable in pixels, differs from the logical size on high density displays
+    fn window_pixel_resize_event(&mut self, _engine: &mut Engine, _width: u32, _height: u32) {}
+    fn window_focus_event(&mut self, _engine: &mut Engine, _focused: bool) {}
+    fn window_minimize_event(&mut self, _engine: &mut Engine, _minimized: bool) {}
+    fn window_move_event(&mut self, _engine: &mut Engine, _x: i32, _y: i32) {}
+    fn display_scale_event(&mut self, _engine: &mut Engine, _scale: f32) {}
+    fn mouse_enter_event(&mut self, _engine: &mut Engine, _entered: bool) {}
 }
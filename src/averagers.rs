@@ -1,74 +1,808 @@
 use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use std::time::Duration;
 
-pub struct CMA {
-    value: f64,
+// The bits of f32/f64 the averagers need, so they aren't stuck with f64
+pub trait Float:
+    Copy
+    + Debug
+    + Default
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn from_usize(n: usize) -> Self;
+    fn from_f64(v: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn sqrt(self) -> Self;
+}
+
+macro_rules! float_impl {
+    ($t:ty) => {
+        impl Float for $t {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+
+            fn from_usize(n: usize) -> Self {
+                n as $t
+            }
+
+            fn from_f64(v: f64) -> Self {
+                v as $t
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn sqrt(self) -> Self {
+                <$t>::sqrt(self)
+            }
+        }
+    };
+}
+
+float_impl!(f32);
+float_impl!(f64);
+
+pub trait Averager<T: Float = f64> {
+    fn update(&mut self, value: T);
+    fn value(&self) -> T;
+    fn reset(&mut self);
+    // Number of samples the current value is based on, capped at the window size for windowed
+    // averagers
+    fn count(&self) -> usize;
+}
+
+// Cumulative moving average, every sample since the last reset weighs the same
+#[derive(Clone, Debug, Default)]
+pub struct CMA<T: Float = f64> {
+    value: T,
     sample_count: usize,
 }
 
-impl CMA {
+impl<T: Float> CMA<T> {
     pub fn new() -> Self {
         Self {
-            value: 0.0,
+            value: T::ZERO,
             sample_count: 0,
         }
     }
+}
 
-    pub fn update(&mut self, value: f64) {
+impl<T: Float> Averager<T> for CMA<T> {
+    fn update(&mut self, value: T) {
         self.sample_count += 1;
-        self.value += (value - self.value) / self.sample_count as f64;
+        self.value += (value - self.value) / T::from_usize(self.sample_count);
     }
 
-    pub fn value(&self) -> f64 {
+    fn value(&self) -> T {
         self.value
     }
+
+    fn reset(&mut self) {
+        self.value = T::ZERO;
+        self.sample_count = 0;
+    }
+
+    fn count(&self) -> usize {
+        self.sample_count
+    }
 }
 
-pub struct EMA {
-    pub alpha: f64,
-    value: f64,
+// Exponential moving average, alpha in (0, 1] is how much the newest sample counts. Starts from 0.
+#[derive(Clone, Debug)]
+pub struct EMA<T: Float = f64> {
+    pub alpha: T,
+    value: T,
+    sample_count: usize,
 }
 
-impl EMA {
-    pub fn new(alpha: f64) -> Self {
-        Self { alpha, value: 0.0 }
+impl<T: Float> EMA<T> {
+    pub fn new(alpha: T) -> Self {
+        Self {
+            alpha,
+            value: T::ZERO,
+            sample_count: 0,
+        }
     }
+}
 
-    pub fn update(&mut self, value: f64) {
-        self.value = self.alpha * value + (1.0 - self.alpha) * self.value;
+impl<T: Float> Averager<T> for EMA<T> {
+    fn update(&mut self, value: T) {
+        self.sample_count += 1;
+        self.value = self.alpha * value + (T::ONE - self.alpha) * self.value;
     }
 
-    pub fn value(&self) -> f64 {
+    fn value(&self) -> T {
         self.value
     }
+
+    fn reset(&mut self) {
+        self.value = T::ZERO;
+        self.sample_count = 0;
+    }
+
+    fn count(&self) -> usize {
+        self.sample_count
+    }
 }
 
-pub struct SMA {
-    pub sample_count: usize,
-    samples: VecDeque<f64>,
-    value: f64,
+// Simple moving average over the last `window` samples
+#[derive(Clone, Debug)]
+pub struct SMA<T: Float = f64> {
+    window: usize,
+    samples: VecDeque<T>,
+    sum: T,
 }
 
-impl SMA {
-    pub fn new(sample_count: usize) -> Self {
+impl<T: Float> SMA<T> {
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
         Self {
-            sample_count,
-            samples: Default::default(),
-            value: 0.0,
+            window,
+            samples: VecDeque::with_capacity(window),
+            sum: T::ZERO,
         }
     }
 
-    pub fn update(&mut self, value: f64) {
+    pub fn window(&self) -> usize {
+        self.window
+    }
+}
+
+impl<T: Float> Averager<T> for SMA<T> {
+    fn update(&mut self, value: T) {
+        if self.samples.len() == self.window {
+            self.sum -= self.samples.pop_front().unwrap();
+        }
         self.samples.push_back(value);
-        if self.samples.len() <= self.sample_count {
-            self.value += (value - self.value) / self.samples.len() as f64;
+        self.sum += value;
+    }
+
+    fn value(&self) -> T {
+        if self.samples.is_empty() {
+            T::ZERO
         } else {
-            self.value +=
-                (1.0 / self.sample_count as f64) * (value - self.samples.front().unwrap());
-            self.samples.pop_back();
+            self.sum / T::from_usize(self.samples.len())
         }
     }
 
-    pub fn value(&self) -> f64 {
-        self.value
+    fn reset(&mut self) {
+        self.samples.clear();
+        self.sum = T::ZERO;
+    }
+
+    fn count(&self) -> usize {
+        self.samples.len()
+    }
+}
+
+// Linearly weighted moving average, the newest of n samples weighs n, the oldest 1
+#[derive(Clone, Debug)]
+pub struct WMA<T: Float = f64> {
+    window: usize,
+    samples: VecDeque<T>,
+    sum: T,
+    // Sum of each sample times its weight
+    weighted_sum: T,
+}
+
+impl<T: Float> WMA<T> {
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            samples: VecDeque::with_capacity(window),
+            sum: T::ZERO,
+            weighted_sum: T::ZERO,
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+}
+
+impl<T: Float> Averager<T> for WMA<T> {
+    fn update(&mut self, value: T) {
+        if self.samples.len() == self.window {
+            // Every weight drops by one, which takes the oldest sample down to 0
+            self.weighted_sum -= self.sum;
+            self.sum -= self.samples.pop_front().unwrap();
+        }
+        self.samples.push_back(value);
+        self.sum += value;
+        self.weighted_sum += T::from_usize(self.samples.len()) * value;
+    }
+
+    fn value(&self) -> T {
+        let n = self.samples.len();
+        if n == 0 {
+            T::ZERO
+        } else {
+            self.weighted_sum / T::from_usize(n * (n + 1) / 2)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.samples.clear();
+        self.sum = T::ZERO;
+        self.weighted_sum = T::ZERO;
+    }
+
+    fn count(&self) -> usize {
+        self.samples.len()
+    }
+}
+
+// Median of the last `window` samples, the mean of the middle two for an even count. NaNs end up
+// sorted after everything else.
+#[derive(Clone, Debug)]
+pub struct MovingMedian<T: Float = f64> {
+    window: usize,
+    samples: VecDeque<T>,
+    sorted: Vec<T>,
+}
+
+impl<T: Float> MovingMedian<T> {
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            samples: VecDeque::with_capacity(window),
+            sorted: Vec::with_capacity(window),
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    fn sorted_position(&self, value: T) -> usize {
+        self.sorted.partition_point(|v| v < &value)
+    }
+}
+
+impl<T: Float> Averager<T> for MovingMedian<T> {
+    fn update(&mut self, value: T) {
+        if self.samples.len() == self.window {
+            let oldest = self.samples.pop_front().unwrap();
+            let index = self.sorted_position(oldest);
+            // partition_point can't find NaNs, fall back to searching for them
+            let index = if self.sorted.get(index) == Some(&oldest) {
+                index
+            } else {
+                self.sorted.iter().position(|v| is_nan(*v)).unwrap()
+            };
+            self.sorted.remove(index);
+        }
+
+        self.samples.push_back(value);
+        let index = if !is_nan(value) {
+            self.sorted_position(value)
+        } else {
+            self.sorted.len()
+        };
+        self.sorted.insert(index, value);
+    }
+
+    fn value(&self) -> T {
+        let n = self.sorted.len();
+        if n == 0 {
+            T::ZERO
+        } else if n % 2 == 1 {
+            self.sorted[n / 2]
+        } else {
+            (self.sorted[n / 2 - 1] + self.sorted[n / 2]) / T::from_usize(2)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.samples.clear();
+        self.sorted.clear();
+    }
+
+    fn count(&self) -> usize {
+        self.samples.len()
+    }
+}
+
+fn is_nan<T: Float>(v: T) -> bool {
+    v.partial_cmp(&v).is_none()
+}
+
+// Monotonic deque, each candidate is dropped as soon as a newer sample beats it, so the front is
+// always the extremum of the window and every update is amortized O(1)
+#[derive(Clone, Debug)]
+struct MonotonicWindow<T: Float> {
+    window: usize,
+    // Sample index and value
+    candidates: VecDeque<(usize, T)>,
+    next_index: usize,
+    sample_count: usize,
+}
+
+impl<T: Float> MonotonicWindow<T> {
+    fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            candidates: VecDeque::default(),
+            next_index: 0,
+            sample_count: 0,
+        }
+    }
+
+    // beats(a, b) is true when a makes b irrelevant, i.e. a <= b for a minimum
+    fn update(&mut self, value: T, beats: fn(T, T) -> bool) {
+        while self
+            .candidates
+            .back()
+            .is_some_and(|&(_, candidate)| beats(value, candidate))
+        {
+            self.candidates.pop_back();
+        }
+        self.candidates.push_back((self.next_index, value));
+
+        while self
+            .candidates
+            .front()
+            .is_some_and(|&(index, _)| index + self.window <= self.next_index)
+        {
+            self.candidates.pop_front();
+        }
+
+        self.next_index += 1;
+        self.sample_count = (self.sample_count + 1).min(self.window);
+    }
+
+    fn value(&self) -> T {
+        self.candidates.front().map_or(T::ZERO, |&(_, v)| v)
+    }
+
+    fn reset(&mut self) {
+        self.candidates.clear();
+        self.next_index = 0;
+        self.sample_count = 0;
+    }
+}
+
+// Smallest of the last `window` samples
+#[derive(Clone, Debug)]
+pub struct MovingMin<T: Float = f64>(MonotonicWindow<T>);
+
+impl<T: Float> MovingMin<T> {
+    pub fn new(window: usize) -> Self {
+        Self(MonotonicWindow::new(window))
+    }
+
+    pub fn window(&self) -> usize {
+        self.0.window
+    }
+}
+
+impl<T: Float> Averager<T> for MovingMin<T> {
+    fn update(&mut self, value: T) {
+        self.0.update(value, |a, b| a <= b);
+    }
+
+    fn value(&self) -> T {
+        self.0.value()
+    }
+
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+
+    fn count(&self) -> usize {
+        self.0.sample_count
+    }
+}
+
+// Largest of the last `window` samples
+#[derive(Clone, Debug)]
+pub struct MovingMax<T: Float = f64>(MonotonicWindow<T>);
+
+impl<T: Float> MovingMax<T> {
+    pub fn new(window: usize) -> Self {
+        Self(MonotonicWindow::new(window))
+    }
+
+    pub fn window(&self) -> usize {
+        self.0.window
+    }
+}
+
+impl<T: Float> Averager<T> for MovingMax<T> {
+    fn update(&mut self, value: T) {
+        self.0.update(value, |a, b| a >= b);
+    }
+
+    fn value(&self) -> T {
+        self.0.value()
+    }
+
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+
+    fn count(&self) -> usize {
+        self.0.sample_count
+    }
+}
+
+// Running mean and variance over every sample since the last reset, using Welford's algorithm so
+// it stays accurate when the variance is small next to the mean. value() is the mean.
+#[derive(Clone, Debug, Default)]
+pub struct Welford<T: Float = f64> {
+    sample_count: usize,
+    mean: T,
+    // Sum of squared differences from the mean
+    m2: T,
+}
+
+impl<T: Float> Welford<T> {
+    pub fn new() -> Self {
+        Self {
+            sample_count: 0,
+            mean: T::ZERO,
+            m2: T::ZERO,
+        }
+    }
+
+    pub fn mean(&self) -> T {
+        self.mean
+    }
+
+    // Population variance, dividing by n
+    pub fn variance(&self) -> T {
+        if self.sample_count == 0 {
+            T::ZERO
+        } else {
+            self.m2 / T::from_usize(self.sample_count)
+        }
+    }
+
+    // Unbiased estimate from a sample, dividing by n - 1
+    pub fn sample_variance(&self) -> T {
+        if self.sample_count < 2 {
+            T::ZERO
+        } else {
+            self.m2 / T::from_usize(self.sample_count - 1)
+        }
+    }
+
+    pub fn std_dev(&self) -> T {
+        self.variance().sqrt()
+    }
+
+    pub fn sample_std_dev(&self) -> T {
+        self.sample_variance().sqrt()
+    }
+}
+
+impl<T: Float> Averager<T> for Welford<T> {
+    fn update(&mut self, value: T) {
+        self.sample_count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / T::from_usize(self.sample_count);
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn value(&self) -> T {
+        self.mean
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    fn count(&self) -> usize {
+        self.sample_count
+    }
+}
+
+// Mean of the samples from the last `window` of time. Timestamps are whatever clock the caller
+// uses (e.g. Clock::now()) and have to be non-decreasing, a sample at t stays in until
+// t + window.
+#[derive(Clone, Debug)]
+pub struct TimeWindowedMean<T: Float = f64> {
+    window: Duration,
+    samples: VecDeque<(Duration, T)>,
+    sum: T,
+}
+
+impl<T: Float> TimeWindowedMean<T> {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::default(),
+            sum: T::ZERO,
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    pub fn update(&mut self, at: Duration, value: T) {
+        debug_assert!(self.samples.back().is_none_or(|(last, _)| *last <= at));
+        self.samples.push_back((at, value));
+        self.sum += value;
+        self.expire(at);
+    }
+
+    // Drops samples that have left the window by now, without adding a new one
+    pub fn expire(&mut self, now: Duration) {
+        while self
+            .samples
+            .front()
+            .is_some_and(|(t, _)| *t + self.window <= now)
+        {
+            self.sum -= self.samples.pop_front().unwrap().1;
+        }
+        // Don't let rounding errors from the running sum outlive the samples
+        if self.samples.is_empty() {
+            self.sum = T::ZERO;
+        }
+    }
+
+    pub fn value(&self) -> T {
+        if self.samples.is_empty() {
+            T::ZERO
+        } else {
+            self.sum / T::from_usize(self.samples.len())
+        }
+    }
+
+    pub fn sum(&self) -> T {
+        self.sum
+    }
+
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.sum = T::ZERO;
+    }
+}
+
+// Amount per second over the last `window` of time, e.g. bytes received or clicks (amount 1 each).
+// The rate is always over the whole window, so it ramps up over the first window and decays back
+// to 0 once records stop.
+#[derive(Clone, Debug)]
+pub struct TimeWindowedRate<T: Float = f64> {
+    amounts: TimeWindowedMean<T>,
+}
+
+impl<T: Float> TimeWindowedRate<T> {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            amounts: TimeWindowedMean::new(window),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.amounts.window
+    }
+
+    pub fn record(&mut self, at: Duration, amount: T) {
+        self.amounts.update(at, amount);
+    }
+
+    pub fn rate(&mut self, now: Duration) -> T {
+        self.amounts.expire(now);
+        let window = self.amounts.window.as_secs_f64();
+        if window == 0.0 {
+            T::ZERO
+        } else {
+            self.amounts.sum / T::from_f64(window)
+        }
+    }
+
+    // Total amount currently in the window
+    pub fn total(&self) -> T {
+        self.amounts.sum
+    }
+
+    pub fn count(&self) -> usize {
+        self.amounts.count()
+    }
+
+    pub fn reset(&mut self) {
+        self.amounts.reset();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    fn feed<A: Averager<f64>>(averager: &mut A, values: &[f64]) -> Vec<f64> {
+        values
+            .iter()
+            .map(|v| {
+                averager.update(*v);
+                averager.value()
+            })
+            .collect()
+    }
+
+    fn assert_all_relative_eq(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert_relative_eq!(a, e, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn cma() {
+        let mut cma = CMA::new();
+        assert_all_relative_eq(&feed(&mut cma, &[2.0, 4.0, 9.0]), &[2.0, 3.0, 5.0]);
+        assert_eq!(cma.count(), 3);
+
+        cma.reset();
+        assert_eq!((cma.value(), cma.count()), (0.0, 0));
+    }
+
+    #[test]
+    fn ema() {
+        let mut ema = EMA::new(0.5);
+        assert_all_relative_eq(&feed(&mut ema, &[4.0, 4.0, 8.0]), &[2.0, 3.0, 5.5]);
+        assert_eq!(ema.count(), 3);
+    }
+
+    #[test]
+    fn sma_drops_oldest() {
+        let mut sma = SMA::new(3);
+        assert_all_relative_eq(
+            &feed(&mut sma, &[1.0, 2.0, 3.0, 10.0, 20.0]),
+            &[1.0, 1.5, 2.0, 5.0, 11.0],
+        );
+        assert_eq!(sma.count(), 3);
+
+        sma.reset();
+        assert_eq!((sma.value(), sma.count()), (0.0, 0));
+    }
+
+    #[test]
+    fn sma_f32() {
+        let mut sma = SMA::<f32>::new(2);
+        sma.update(1.0);
+        sma.update(2.0);
+        sma.update(4.0);
+        assert_relative_eq!(sma.value(), 3.0f32);
+    }
+
+    #[test]
+    fn wma() {
+        // Window of 3: (1*a + 2*b + 3*c) / 6 once full
+        let mut wma = WMA::new(3);
+        assert_all_relative_eq(
+            &feed(&mut wma, &[3.0, 6.0, 9.0, 12.0, 0.0]),
+            &[3.0, 5.0, 7.0, 10.0, 5.5],
+        );
+        assert_eq!(wma.count(), 3);
+    }
+
+    #[test]
+    fn moving_median() {
+        let mut median = MovingMedian::new(4);
+        assert_all_relative_eq(
+            &feed(&mut median, &[5.0, 1.0, 9.0, 3.0, 3.0, 100.0, 2.0]),
+            &[5.0, 3.0, 5.0, 4.0, 3.0, 6.0, 3.0],
+        );
+        assert_eq!(median.count(), 4);
+    }
+
+    #[test]
+    fn moving_median_with_nan() {
+        let mut median = MovingMedian::new(2);
+        median.update(f64::NAN);
+        median.update(1.0);
+        median.update(2.0);
+        median.update(3.0);
+        assert_relative_eq!(median.value(), 2.5);
+    }
+
+    #[test]
+    fn moving_min_max() {
+        let values = [4.0, 2.0, 12.0, 3.0, 5.0, 1.0, 7.0, 7.0, 8.0];
+
+        let mut min = MovingMin::new(3);
+        assert_all_relative_eq(
+            &feed(&mut min, &values),
+            &[4.0, 2.0, 2.0, 2.0, 3.0, 1.0, 1.0, 1.0, 7.0],
+        );
+
+        let mut max = MovingMax::new(3);
+        assert_all_relative_eq(
+            &feed(&mut max, &values),
+            &[4.0, 4.0, 12.0, 12.0, 12.0, 5.0, 7.0, 7.0, 8.0],
+        );
+        assert_eq!(max.count(), 3);
+
+        max.reset();
+        max.update(-1.0);
+        assert_eq!((max.value(), max.count()), (-1.0, 1));
+    }
+
+    #[test]
+    fn welford() {
+        // Classic example with a population std dev of exactly 2
+        let mut welford = Welford::new();
+        feed(&mut welford, &[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!(welford.count(), 8);
+        assert_relative_eq!(welford.mean(), 5.0);
+        assert_relative_eq!(welford.variance(), 4.0);
+        assert_relative_eq!(welford.std_dev(), 2.0);
+        assert_relative_eq!(welford.sample_variance(), 32.0 / 7.0);
+    }
+
+    #[test]
+    fn welford_large_offset() {
+        // Naive sum of squares loses everything here, Welford shouldn't
+        let mut welford = Welford::new();
+        feed(
+            &mut welford,
+            &[1e9 + 4.0, 1e9 + 7.0, 1e9 + 13.0, 1e9 + 16.0],
+        );
+        assert_relative_eq!(welford.mean(), 1e9 + 10.0);
+        assert_relative_eq!(welford.sample_variance(), 30.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn time_windowed_mean() {
+        let ms = Duration::from_millis;
+        let mut mean = TimeWindowedMean::new(ms(100));
+
+        mean.update(ms(0), 1.0);
+        mean.update(ms(50), 2.0);
+        mean.update(ms(99), 6.0);
+        assert_relative_eq!(mean.value(), 3.0);
+        assert_eq!(mean.count(), 3);
+
+        // The sample at 0 leaves exactly at 100
+        mean.update(ms(100), 4.0);
+        assert_relative_eq!(mean.value(), 4.0);
+        assert_relative_eq!(mean.sum(), 12.0);
+
+        mean.expire(ms(199));
+        assert_relative_eq!(mean.value(), 4.0);
+        assert_eq!(mean.count(), 1);
+
+        mean.expire(ms(1000));
+        assert_eq!((mean.value(), mean.count()), (0.0, 0));
+    }
+
+    #[test]
+    fn time_windowed_rate() {
+        let ms = Duration::from_millis;
+        let mut clicks = TimeWindowedRate::new(ms(500));
+
+        for t in (0..500).step_by(50) {
+            clicks.record(ms(t), 1.0);
+        }
+        assert_relative_eq!(clicks.rate(ms(450)), 20.0);
+
+        clicks.record(ms(500), 3.0);
+        assert_relative_eq!(clicks.total(), 12.0);
+        assert_relative_eq!(clicks.rate(ms(500)), 24.0);
+
+        // Decays as samples leave the window
+        assert_relative_eq!(clicks.rate(ms(900)), 8.0);
+        assert_relative_eq!(clicks.rate(ms(1000)), 0.0);
+        assert_eq!(clicks.count(), 0);
     }
 }
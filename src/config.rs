@@ -0,0 +1,103 @@
+use crate::gfx::GlColor;
+use sdl3::video::WindowBuilder;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GlProfile {
+    Core,
+    Compatibility,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VsyncMode {
+    Off,
+    On,
+    // Tears instead of waiting when a frame misses the vblank, falls back to On if unsupported
+    Adaptive,
+}
+
+// Boxed with the config's lifetime so the closure can borrow from the caller
+type WindowBuildFn<'a> = Box<dyn FnOnce(&mut WindowBuilder) -> &mut WindowBuilder + 'a>;
+
+pub struct EngineConfig<'a> {
+    pub(crate) title: String,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) gl_version: (u8, u8),
+    pub(crate) gl_profile: GlProfile,
+    pub(crate) msaa_samples: u8,
+    pub(crate) vsync: VsyncMode,
+    pub(crate) clear_color: (f32, f32, f32, f32),
+    pub(crate) debug_context: bool,
+    pub(crate) install_logger: bool,
+    pub(crate) headless: bool,
+    pub(crate) window_build_fn: Option<WindowBuildFn<'a>>,
+}
+
+impl<'a> EngineConfig<'a> {
+    pub fn new(title: &str, width: u32, height: u32) -> Self {
+        Self {
+            title: title.to_owned(),
+            width,
+            height,
+            gl_version: (3, 3),
+            gl_profile: GlProfile::Core,
+            msaa_samples: 0,
+            vsync: VsyncMode::On,
+            clear_color: (0.0, 0.0, 0.0, 1.0),
+            debug_context: cfg!(debug_assertions),
+            install_logger: true,
+            headless: false,
+            window_build_fn: None,
+        }
+    }
+
+    pub fn with_gl_version(mut self, major: u8, minor: u8) -> Self {
+        self.gl_version = (major, minor);
+        self
+    }
+
+    pub fn with_gl_profile(mut self, profile: GlProfile) -> Self {
+        self.gl_profile = profile;
+        self
+    }
+
+    // 0 disables multisampling, only applies to the window's default framebuffer
+    pub fn with_msaa(mut self, samples: u8) -> Self {
+        self.msaa_samples = samples;
+        self
+    }
+
+    pub fn with_vsync(mut self, mode: VsyncMode) -> Self {
+        self.vsync = mode;
+        self
+    }
+
+    pub fn with_clear_color<T: GlColor>(mut self, color: &T) -> Self {
+        self.clear_color = color.gl_color();
+        self
+    }
+
+    pub fn with_debug_context(mut self, enabled: bool) -> Self {
+        self.debug_context = enabled;
+        self
+    }
+
+    // Turn this off if the application sets up its own `log` implementation
+    pub fn with_logger(mut self, enabled: bool) -> Self {
+        self.install_logger = enabled;
+        self
+    }
+
+    pub fn with_headless(mut self, enabled: bool) -> Self {
+        self.headless = enabled;
+        self
+    }
+
+    pub fn with_window<T>(mut self, window_build_fn: T) -> Self
+    where
+        T: FnOnce(&mut WindowBuilder) -> &mut WindowBuilder + 'a,
+    {
+        self.window_build_fn = Some(Box::new(window_build_fn));
+        self
+    }
+}
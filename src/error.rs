@@ -0,0 +1,90 @@
+use crate::{
+    gl::types::GLenum,
+    hlgl::{ShaderDiagnostic, ShaderKind},
+};
+use std::fmt::{Display, Formatter};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    SdlInit(String),
+    WindowCreation(String),
+    GlContext(String),
+    FramebufferIncomplete(GLenum),
+    ShaderCompile {
+        stage: ShaderKind,
+        source_name: String,
+        diagnostics: Vec<ShaderDiagnostic>,
+    },
+    ShaderLink {
+        diagnostics: Vec<ShaderDiagnostic>,
+    },
+    BindingParse(String),
+    Cursor(String),
+    Clipboard(String),
+    RecordingFormat(String),
+    ColorParse(String),
+    Io(std::io::Error),
+    PngEncode(png::EncodingError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::SdlInit(msg) => write!(f, "Failed to initialize SDL: {msg}"),
+            Error::WindowCreation(msg) => write!(f, "Failed to create window: {msg}"),
+            Error::GlContext(msg) => write!(f, "Failed to set up GL context: {msg}"),
+            Error::FramebufferIncomplete(status) => {
+                write!(f, "Framebuffer is incomplete (status: {status:#x})")
+            }
+            Error::ShaderCompile {
+                stage,
+                source_name,
+                diagnostics,
+            } => {
+                write!(f, "Failed to compile {stage} shader '{source_name}'")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{diagnostic}")?;
+                }
+                Ok(())
+            }
+            Error::ShaderLink { diagnostics } => {
+                write!(f, "Failed to link shader program")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{diagnostic}")?;
+                }
+                Ok(())
+            }
+            Error::BindingParse(msg) => write!(f, "Failed to parse binding: {msg}"),
+            Error::Cursor(msg) => write!(f, "Failed to create cursor: {msg}"),
+            Error::Clipboard(msg) => write!(f, "Failed to access clipboard: {msg}"),
+            Error::RecordingFormat(msg) => write!(f, "Invalid recording: {msg}"),
+            Error::ColorParse(msg) => write!(f, "Failed to parse color: {msg}"),
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+            Error::PngEncode(e) => write!(f, "Failed to encode PNG: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::PngEncode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<png::EncodingError> for Error {
+    fn from(e: png::EncodingError) -> Self {
+        Error::PngEncode(e)
+    }
+}
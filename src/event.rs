@@ -0,0 +1,301 @@
+use crate::input::{
+    GamepadAction, GamepadAxis, GamepadButton, KeyAction, Keycode, Mod, MouseAction, MouseButton,
+    Scancode,
+};
+use sdl3::EventPump;
+use sdl3::sys::events::{SDL_Event, SDL_PollEvent};
+use std::path::PathBuf;
+
+// Everything the engine dispatches to an Application, decoupled from SDL's event type so it can
+// be recorded, replayed and injected
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Quit,
+
+    Key {
+        keycode: Option<Keycode>,
+        scancode: Option<Scancode>,
+        keymod: Mod,
+        action: KeyAction,
+    },
+    MouseButton {
+        x: f32,
+        y: f32,
+        button: MouseButton,
+        clicks: u8,
+        action: MouseAction,
+    },
+    MouseMotion {
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+    },
+    // Already corrected for flipped scroll directions
+    MouseWheel {
+        x: f32,
+        y: f32,
+        scroll_x: f32,
+        scroll_y: f32,
+    },
+
+    TextInput {
+        text: String,
+    },
+    TextEditing {
+        text: String,
+        start: i32,
+        length: i32,
+    },
+
+    DropBegin,
+    DropFile {
+        path: PathBuf,
+        x: f32,
+        y: f32,
+    },
+    DropText {
+        text: String,
+        x: f32,
+        y: f32,
+    },
+    DropComplete,
+
+    GamepadAdded {
+        id: u32,
+    },
+    GamepadRemoved {
+        id: u32,
+    },
+    GamepadButton {
+        id: u32,
+        button: GamepadButton,
+        action: GamepadAction,
+    },
+    // Raw axis value, dead zones are applied when it's dispatched
+    GamepadAxis {
+        id: u32,
+        axis: GamepadAxis,
+        value: i16,
+    },
+
+    WindowResized {
+        width: u32,
+        height: u32,
+    },
+    WindowPixelResized {
+        width: u32,
+        height: u32,
+    },
+    DisplayChanged,
+    WindowFocus {
+        focused: bool,
+    },
+    WindowMinimized,
+    // Also sent when a window is maximized, which is how SDL restores a minimized maximized window
+    WindowRestored,
+    WindowMoved {
+        x: i32,
+        y: i32,
+    },
+    MouseEnter {
+        entered: bool,
+    },
+}
+
+impl Event {
+    // Like EventPump::poll_event, but keeps the raw event around for from_sdl. Returns None once
+    // the queue is empty and Some(None) for events the engine doesn't handle. The pump is only
+    // taken to make sure the event subsystem is up.
+    pub(crate) fn poll(_event_pump: &mut EventPump) -> Option<Option<Self>> {
+        let mut raw = std::mem::MaybeUninit::<SDL_Event>::uninit();
+        if !unsafe { SDL_PollEvent(raw.as_mut_ptr()) } {
+            return None;
+        }
+        let raw = unsafe { raw.assume_init() };
+        Some(Self::from_sdl(sdl3::event::Event::from_ll(raw), &raw))
+    }
+
+    // Returns None for SDL events the engine doesn't handle. sdl3-rs's Event leaves out some
+    // fields, like where a drop happened, those are read from the raw event.
+    fn from_sdl(event: sdl3::event::Event, raw: &SDL_Event) -> Option<Self> {
+        use sdl3::event::{Event as SdlEvent, WindowEvent};
+
+        Some(match event {
+            SdlEvent::Quit { .. } => Self::Quit,
+
+            SdlEvent::KeyDown {
+                keycode,
+                scancode,
+                keymod,
+                repeat,
+                ..
+            } => Self::Key {
+                keycode,
+                scancode,
+                keymod,
+                action: if repeat {
+                    KeyAction::Repeat
+                } else {
+                    KeyAction::Press
+                },
+            },
+
+            SdlEvent::KeyUp {
+                keycode,
+                scancode,
+                keymod,
+                ..
+            } => Self::Key {
+                keycode,
+                scancode,
+                keymod,
+                action: KeyAction::Release,
+            },
+
+            SdlEvent::MouseButtonDown {
+                x,
+                y,
+                mouse_btn,
+                clicks,
+                ..
+            } => Self::MouseButton {
+                x,
+                y,
+                button: MouseButton::from(mouse_btn),
+                clicks,
+                action: MouseAction::Press,
+            },
+
+            SdlEvent::MouseButtonUp {
+                x,
+                y,
+                mouse_btn,
+                clicks,
+                ..
+            } => Self::MouseButton {
+                x,
+                y,
+                button: MouseButton::from(mouse_btn),
+                clicks,
+                action: MouseAction::Release,
+            },
+
+            SdlEvent::MouseMotion {
+                x, y, xrel, yrel, ..
+            } => Self::MouseMotion {
+                x,
+                y,
+                dx: xrel,
+                dy: yrel,
+            },
+
+            SdlEvent::MouseWheel {
+                x,
+                y,
+                direction,
+                mouse_x,
+                mouse_y,
+                ..
+            } => {
+                let (scroll_x, scroll_y) = match direction {
+                    sdl3::mouse::MouseWheelDirection::Flipped => (-x, -y),
+                    _ => (x, y),
+                };
+                Self::MouseWheel {
+                    x: mouse_x,
+                    y: mouse_y,
+                    scroll_x,
+                    scroll_y,
+                }
+            }
+
+            SdlEvent::TextInput { text, .. } => Self::TextInput { text },
+
+            SdlEvent::TextEditing {
+                text,
+                start,
+                length,
+                ..
+            } => Self::TextEditing {
+                text,
+                start,
+                length,
+            },
+
+            SdlEvent::DropBegin { .. } => Self::DropBegin,
+
+            SdlEvent::DropFile { filename, .. } => {
+                let (x, y) = drop_position(raw);
+                Self::DropFile {
+                    path: PathBuf::from(filename),
+                    x,
+                    y,
+                }
+            }
+
+            SdlEvent::DropText { filename, .. } => {
+                let (x, y) = drop_position(raw);
+                Self::DropText {
+                    text: filename,
+                    x,
+                    y,
+                }
+            }
+
+            SdlEvent::DropComplete { .. } => Self::DropComplete,
+
+            SdlEvent::ControllerDeviceAdded { which, .. } => Self::GamepadAdded { id: which },
+            SdlEvent::ControllerDeviceRemoved { which, .. } => Self::GamepadRemoved { id: which },
+
+            SdlEvent::ControllerButtonDown { which, button, .. } => Self::GamepadButton {
+                id: which,
+                button: GamepadButton::from(button),
+                action: GamepadAction::Press,
+            },
+
+            SdlEvent::ControllerButtonUp { which, button, .. } => Self::GamepadButton {
+                id: which,
+                button: GamepadButton::from(button),
+                action: GamepadAction::Release,
+            },
+
+            SdlEvent::ControllerAxisMotion {
+                which, axis, value, ..
+            } => Self::GamepadAxis {
+                id: which,
+                axis: GamepadAxis::from(axis),
+                value,
+            },
+
+            SdlEvent::Window { win_event, .. } => match win_event {
+                WindowEvent::Resized(width, height) => Self::WindowResized {
+                    width: width as u32,
+                    height: height as u32,
+                },
+                WindowEvent::PixelSizeChanged(width, height) => Self::WindowPixelResized {
+                    width: width as u32,
+                    height: height as u32,
+                },
+                WindowEvent::DisplayChanged(_) => Self::DisplayChanged,
+                WindowEvent::FocusGained => Self::WindowFocus { focused: true },
+                WindowEvent::FocusLost => Self::WindowFocus { focused: false },
+                WindowEvent::Minimized => Self::WindowMinimized,
+                WindowEvent::Restored | WindowEvent::Maximized => Self::WindowRestored,
+                WindowEvent::Moved(x, y) => Self::WindowMoved { x, y },
+                WindowEvent::MouseEnter => Self::MouseEnter { entered: true },
+                WindowEvent::MouseLeave => Self::MouseEnter { entered: false },
+                _ => return None,
+            },
+
+            _ => return None,
+        })
+    }
+}
+
+// Relative to the window the drop happened in
+fn drop_position(raw: &SDL_Event) -> (f32, f32) {
+    // Only called for drop events, so that's the active union field
+    let drop = unsafe { raw.drop };
+    (drop.x, drop.y)
+}
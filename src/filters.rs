@@ -0,0 +1,335 @@
+use crate::Float;
+use std::f32::consts::PI;
+use std::fmt::Debug;
+use std::ops::{Add, Sub};
+
+// Anything the filters can smooth: scalars and glm vectors. Filter parameters and dt are f32
+// seconds like the dt passed to Application::update.
+pub trait Signal: Copy + Debug + Add<Output = Self> + Sub<Output = Self> {
+    fn zero() -> Self;
+    fn scale(self, k: f32) -> Self;
+    fn magnitude(self) -> f32;
+}
+
+// Scalars come from the same Float the averagers use
+impl<T: Float> Signal for T {
+    fn zero() -> Self {
+        T::ZERO
+    }
+
+    fn scale(self, k: f32) -> Self {
+        self * T::from_f64(k as f64)
+    }
+
+    fn magnitude(self) -> f32 {
+        self.to_f64().abs() as f32
+    }
+}
+
+macro_rules! signal_vec_impl {
+    ($t:ty) => {
+        impl Signal for $t {
+            fn zero() -> Self {
+                <$t>::zeros()
+            }
+
+            fn scale(self, k: f32) -> Self {
+                self * k
+            }
+
+            fn magnitude(self) -> f32 {
+                self.norm()
+            }
+        }
+    };
+}
+
+signal_vec_impl!(glm::Vec2);
+signal_vec_impl!(glm::Vec3);
+
+fn lerp<T: Signal>(a: T, b: T, t: f32) -> T {
+    a + (b - a).scale(t)
+}
+
+// Smoothing factor of a first order low-pass with the given cutoff in Hz
+fn low_pass_alpha(cutoff: f32, dt: f32) -> f32 {
+    let tau = 1.0 / (2.0 * PI * cutoff);
+    1.0 / (1.0 + tau / dt)
+}
+
+// One Euro filter (Casiez et al. 2012). Heavy smoothing while the input is slow, which hides
+// jitter, and less as it speeds up, which keeps lag down. Lower min_cutoff for less jitter, raise
+// beta for less lag.
+#[derive(Clone, Debug)]
+pub struct OneEuro<T: Signal> {
+    pub min_cutoff: f32,
+    pub beta: f32,
+    pub derivative_cutoff: f32,
+    // Last filtered value and derivative
+    state: Option<(T, T)>,
+}
+
+impl<T: Signal> OneEuro<T> {
+    pub fn new(min_cutoff: f32, beta: f32) -> Self {
+        Self {
+            min_cutoff,
+            beta,
+            derivative_cutoff: 1.0,
+            state: None,
+        }
+    }
+
+    pub fn with_derivative_cutoff(mut self, cutoff: f32) -> Self {
+        self.derivative_cutoff = cutoff;
+        self
+    }
+
+    // The first value passes through unchanged
+    pub fn update(&mut self, value: T, dt: f32) -> T {
+        let (prev, prev_derivative) = match self.state {
+            Some(state) if dt > 0.0 => state,
+            Some((prev, _)) => return prev,
+            None => {
+                self.state = Some((value, T::zero()));
+                return value;
+            }
+        };
+
+        let derivative = lerp(
+            prev_derivative,
+            (value - prev).scale(1.0 / dt),
+            low_pass_alpha(self.derivative_cutoff, dt),
+        );
+        let cutoff = self.min_cutoff + self.beta * derivative.magnitude();
+        let filtered = lerp(prev, value, low_pass_alpha(cutoff, dt));
+
+        self.state = Some((filtered, derivative));
+        filtered
+    }
+
+    pub fn value(&self) -> Option<T> {
+        self.state.map(|(value, _)| value)
+    }
+
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+// Critically damped spring towards a moving target, like Unity's SmoothDamp. Reaches the target
+// in roughly smooth_time seconds without overshooting it. Uses the exact solution, so the result
+// doesn't depend on how the time is split into frames.
+#[derive(Clone, Debug)]
+pub struct SmoothDamp<T: Signal> {
+    pub smooth_time: f32,
+    value: T,
+    velocity: T,
+}
+
+impl<T: Signal> SmoothDamp<T> {
+    pub fn new(value: T, smooth_time: f32) -> Self {
+        Self {
+            smooth_time,
+            value,
+            velocity: T::zero(),
+        }
+    }
+
+    pub fn update(&mut self, target: T, dt: f32) -> T {
+        if dt <= 0.0 {
+            return self.value;
+        }
+        let omega = 2.0 / self.smooth_time.max(1e-4);
+        let decay = (-omega * dt).exp();
+
+        let offset = self.value - target;
+        let temp = (self.velocity + offset.scale(omega)).scale(dt);
+        self.velocity = (self.velocity - temp.scale(omega)).scale(decay);
+        self.value = target + (offset + temp).scale(decay);
+        self.value
+    }
+
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    pub fn velocity(&self) -> T {
+        self.velocity
+    }
+
+    // Jumps to value and stops
+    pub fn reset(&mut self, value: T) {
+        self.value = value;
+        self.velocity = T::zero();
+    }
+}
+
+// Second order low-pass, cutoff in Hz. The default damping of 1/sqrt(2) is a Butterworth response,
+// 1.0 is critically damped and lower values ring. Integrated implicitly so it stays stable with
+// long frames.
+#[derive(Clone, Debug)]
+pub struct SecondOrderLowPass<T: Signal> {
+    pub cutoff: f32,
+    pub damping: f32,
+    // Output and its velocity
+    state: Option<(T, T)>,
+}
+
+impl<T: Signal> SecondOrderLowPass<T> {
+    pub fn new(cutoff: f32) -> Self {
+        Self {
+            cutoff,
+            damping: std::f32::consts::FRAC_1_SQRT_2,
+            state: None,
+        }
+    }
+
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    // The first value passes through unchanged
+    pub fn update(&mut self, value: T, dt: f32) -> T {
+        let (prev, velocity) = match self.state {
+            Some(state) if dt > 0.0 => state,
+            Some((prev, _)) => return prev,
+            None => {
+                self.state = Some((value, T::zero()));
+                return value;
+            }
+        };
+
+        let omega = 2.0 * PI * self.cutoff;
+        let k = omega * omega;
+        let velocity = (velocity + (value - prev).scale(dt * k))
+            .scale(1.0 / (1.0 + 2.0 * self.damping * omega * dt + k * dt * dt));
+        let filtered = prev + velocity.scale(dt);
+
+        self.state = Some((filtered, velocity));
+        filtered
+    }
+
+    pub fn value(&self) -> Option<T> {
+        self.state.map(|(value, _)| value)
+    }
+
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    // Deterministic jitter in [-1, 1]
+    fn noise(i: usize) -> f32 {
+        ((i * 7919 % 200) as f32 / 100.0) - 1.0
+    }
+
+    #[test]
+    fn one_euro_smooths_jitter_and_follows_motion() {
+        let mut filter = OneEuro::new(1.0, 0.0);
+        let dt = 1.0 / 60.0;
+
+        assert_eq!(filter.update(10.0, dt), 10.0);
+        let mut max_error: f32 = 0.0;
+        for i in 0..600 {
+            let out = filter.update(10.0 + 0.1 * noise(i), dt);
+            max_error = max_error.max((out - 10.0).abs());
+        }
+        assert!(max_error < 0.05);
+
+        // With beta the lag on a steady movement drops
+        let lag = |beta| {
+            let mut filter = OneEuro::new(1.0, beta);
+            let mut out = 0.0;
+            for i in 0..120 {
+                out = filter.update(i as f32, dt);
+            }
+            119.0 - out
+        };
+        assert!(lag(0.5) < lag(0.0) / 4.0);
+
+        filter.reset();
+        assert_eq!(filter.value(), None);
+    }
+
+    #[test]
+    fn one_euro_vectors() {
+        let mut filter = OneEuro::new(1.0, 0.1);
+        let target = glm::vec2(3.0, -4.0);
+        filter.update(glm::Vec2::zeros(), 0.01);
+        for _ in 0..1000 {
+            filter.update(target, 0.01);
+        }
+        assert_relative_eq!(filter.value().unwrap(), target, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn smooth_damp_matches_closed_form() {
+        // x(t) = (1 + wt)e^(-wt) from rest at 1 towards 0
+        let mut spring = SmoothDamp::new(1.0f64, 0.5);
+        let omega: f64 = 4.0;
+        for _ in 0..30 {
+            spring.update(0.0, 1.0 / 60.0);
+        }
+        let t = 0.5;
+        assert_relative_eq!(
+            spring.value(),
+            (1.0 + omega * t) * (-omega * t).exp(),
+            epsilon = 1e-6
+        );
+
+        // One long step lands in the same place as many short ones
+        let mut long = SmoothDamp::new(1.0f64, 0.5);
+        long.update(0.0, 0.5);
+        assert_relative_eq!(long.value(), spring.value(), epsilon = 1e-6);
+        assert_relative_eq!(long.velocity(), spring.velocity(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn smooth_damp_does_not_overshoot() {
+        let mut spring = SmoothDamp::new(glm::Vec3::zeros(), 0.2);
+        let target = glm::vec3(1.0, 2.0, 3.0);
+        for _ in 0..200 {
+            let value = spring.update(target, 1.0 / 60.0);
+            assert!(value.x <= target.x && value.y <= target.y && value.z <= target.z);
+        }
+        assert_relative_eq!(spring.value(), target, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn second_order_low_pass() {
+        let mut filter = SecondOrderLowPass::new(2.0);
+        assert_eq!(filter.update(0.0f32, 0.01), 0.0);
+
+        // Settles on a step, a Butterworth overshoots slightly on the way
+        let mut peak: f32 = 0.0;
+        for _ in 0..300 {
+            peak = peak.max(filter.update(1.0, 0.01));
+        }
+        assert_relative_eq!(filter.value().unwrap(), 1.0, epsilon = 1e-4);
+        assert!(peak > 1.0 && peak < 1.1);
+
+        // Critically damped doesn't, and stays stable with huge steps
+        let mut filter = SecondOrderLowPass::new(2.0).with_damping(1.0);
+        filter.update(0.0f32, 0.01);
+        for _ in 0..50 {
+            let out = filter.update(1.0, 0.5);
+            assert!((0.0..=1.0).contains(&out));
+        }
+
+        // High frequency noise is damped much more than by the input
+        let mut filter = SecondOrderLowPass::new(1.0);
+        filter.update(0.0f32, 1.0 / 60.0);
+        let mut max_out: f32 = 0.0;
+        for i in 0..600 {
+            let input = if i % 2 == 0 { 1.0 } else { -1.0 };
+            max_out = max_out.max(filter.update(input, 1.0 / 60.0).abs());
+        }
+        assert!(max_out < 0.05);
+    }
+}
@@ -1,6 +1,8 @@
 mod batcher;
 mod color;
 mod g2d;
+mod image;
 
 pub use color::*;
 pub use g2d::*;
+pub use image::*;
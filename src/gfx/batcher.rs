@@ -52,13 +52,15 @@ macro_rules! try_link_and_insert_shader {
     ($shaders:ident, $gl_version:ident, $kind:path, $kind_str:literal) => {
         let version_directive = format!("#version {}{}0 core\n", $gl_version.0, $gl_version.1);
         match ShaderBuilder::default()
-            .with_src(
+            .with_named_src(
                 ShaderKind::Vertex,
+                concat!($kind_str, ".vert"),
                 &(version_directive.clone()
                     + include_str!(concat!("shader_src/", $kind_str, ".vert"))),
             )
-            .with_src(
+            .with_named_src(
                 ShaderKind::Fragment,
+                concat!($kind_str, ".frag"),
                 &(version_directive + include_str!(concat!("shader_src/", $kind_str, ".frag"))),
             )
             .try_link()
@@ -66,8 +68,8 @@ macro_rules! try_link_and_insert_shader {
             Ok(shader) => {
                 $shaders.insert($kind, shader);
             }
-            Err(_) => {
-                log::error!(concat!("Failed to link ", $kind_str, " shader"));
+            Err(e) => {
+                log::error!(concat!("Failed to build ", $kind_str, " shader: {}"), e);
             }
         }
     };
@@ -88,6 +90,8 @@ impl Batcher {
     }
 
     pub fn draw(&mut self, proj: &glm::Mat4, z_max: f32) {
+        crate::profile_gpu_scope!("g2d");
+
         for batch in &mut self.batches {
             if let Some(shader) = self.shaders.get_mut(&batch.kind) {
                 shader.use_program();
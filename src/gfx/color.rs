@@ -1,3 +1,5 @@
+mod css;
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct Rgba {
     r: u8,
@@ -22,6 +24,74 @@ pub struct Hsla {
     a: u8,
 }
 
+// The float color spaces below take alpha as 0-1 and convert through LinRgba, which is what the
+// From impls between any two of them do under the hood.
+
+// sRGB primaries without the sRGB transfer curve, where blending and lighting math belongs.
+// Components aren't clamped, out of gamut colors from other spaces go outside 0-1.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LinRgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+// CIE 1931 XYZ with a D65 white point, Y is luminance with white at 1
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Xyz {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub alpha: f32,
+}
+
+// CIE L*a*b* relative to D65 white, l is 0-100
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Lab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+// Polar Lab, hue in degrees
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Lch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub alpha: f32,
+}
+
+// Björn Ottosson's perceptual space, l is 0-1. Evenly spaced steps here look evenly spaced, so
+// it's the one to use for palettes and gradients.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+// Polar Oklab, hue in degrees
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Oklch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub alpha: f32,
+}
+
+// Hue, whiteness, blackness, hue in degrees
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hwb {
+    pub h: f32,
+    pub w: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
 impl Rgba {
     pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
         Self { r, g, b, a }
@@ -35,6 +105,11 @@ impl Rgba {
             (v & 0xff) as u8,
         )
     }
+
+    // 0xrrggbbaa, the inverse of hex
+    pub fn to_hex(&self) -> u64 {
+        u64::from(u32::from_be_bytes([self.r, self.g, self.b, self.a]))
+    }
 }
 
 impl Hsva {
@@ -57,35 +132,65 @@ impl Hsla {
     }
 }
 
+impl LinRgba {
+    pub fn new(r: f32, g: f32, b: f32, alpha: f32) -> Self {
+        Self { r, g, b, alpha }
+    }
+}
+
+impl Xyz {
+    pub fn new(x: f32, y: f32, z: f32, alpha: f32) -> Self {
+        Self { x, y, z, alpha }
+    }
+}
+
+impl Lab {
+    pub fn new(l: f32, a: f32, b: f32, alpha: f32) -> Self {
+        Self { l, a, b, alpha }
+    }
+}
+
+impl Lch {
+    pub fn new(l: f32, c: f32, h: f32, alpha: f32) -> Self {
+        Self { l, c, h, alpha }
+    }
+}
+
+impl Oklab {
+    pub fn new(l: f32, a: f32, b: f32, alpha: f32) -> Self {
+        Self { l, a, b, alpha }
+    }
+}
+
+impl Oklch {
+    pub fn new(l: f32, c: f32, h: f32, alpha: f32) -> Self {
+        Self { l, c, h, alpha }
+    }
+}
+
+impl Hwb {
+    pub fn new(h: f32, w: f32, b: f32, alpha: f32) -> Self {
+        Self { h, w, b, alpha }
+    }
+}
+
 impl From<Hsva> for Rgba {
     fn from(hsva: Hsva) -> Self {
-        let c = hsva.v * hsva.s;
-        let m = hsva.v - c;
-        let (r, g, b) = rgb_from_hcm(hsva.h, c, m);
-
-        Self::new(r, g, b, hsva.a)
+        let (r, g, b) = srgb_from_hsv(hsva.h, hsva.s, hsva.v);
+        Self::new(to_u8(r), to_u8(g), to_u8(b), hsva.a)
     }
 }
 
 impl From<Hsla> for Rgba {
     fn from(hsla: Hsla) -> Self {
-        let c = (1.0 - (2.0 * hsla.l - 1.0).abs()) * hsla.s;
-        let m = hsla.l - (c / 2.0);
-        let (r, g, b) = rgb_from_hcm(hsla.h, c, m);
-
-        Self::new(r, g, b, hsla.a)
+        let (r, g, b) = srgb_from_hsl(hsla.h, hsla.s, hsla.l);
+        Self::new(to_u8(r), to_u8(g), to_u8(b), hsla.a)
     }
 }
 
 impl From<Rgba> for Hsva {
     fn from(rgba: Rgba) -> Self {
-        let r = rgba.r as f32 / 255.0;
-        let g = rgba.g as f32 / 255.0;
-        let b = rgba.b as f32 / 255.0;
-
-        let (h, v, c) = hue_from_rgb(r, g, b);
-        let s = if v == 0.0 { 0.0 } else { c / v };
-
+        let (h, s, v) = hsv_from_srgb(from_u8(rgba.r), from_u8(rgba.g), from_u8(rgba.b));
         Self::new(h, s, v, rgba.a)
     }
 }
@@ -105,18 +210,7 @@ impl From<Hsla> for Hsva {
 
 impl From<Rgba> for Hsla {
     fn from(rgba: Rgba) -> Self {
-        let r = rgba.r as f32 / 255.0;
-        let g = rgba.g as f32 / 255.0;
-        let b = rgba.b as f32 / 255.0;
-
-        let (h, v, c) = hue_from_rgb(r, g, b);
-        let l = v - c / 2.0;
-        let s = if l == 0.0 || l == 1.0 {
-            0.0
-        } else {
-            c / (1.0 - (2.0 * v - c - 1.0).abs())
-        };
-
+        let (h, s, l) = hsl_from_srgb(from_u8(rgba.r), from_u8(rgba.g), from_u8(rgba.b));
         Self::new(h, s, l, rgba.a)
     }
 }
@@ -134,6 +228,219 @@ impl From<Hsva> for Hsla {
     }
 }
 
+impl From<Rgba> for LinRgba {
+    fn from(rgba: Rgba) -> Self {
+        Self::new(
+            srgb_decode(from_u8(rgba.r)),
+            srgb_decode(from_u8(rgba.g)),
+            srgb_decode(from_u8(rgba.b)),
+            from_u8(rgba.a),
+        )
+    }
+}
+
+impl From<LinRgba> for Rgba {
+    fn from(lin: LinRgba) -> Self {
+        Self::new(
+            to_u8(srgb_encode(lin.r)),
+            to_u8(srgb_encode(lin.g)),
+            to_u8(srgb_encode(lin.b)),
+            to_u8(lin.alpha),
+        )
+    }
+}
+
+impl From<Hsva> for LinRgba {
+    fn from(hsva: Hsva) -> Self {
+        let (r, g, b) = srgb_from_hsv(hsva.h, hsva.s, hsva.v);
+        Self::new(
+            srgb_decode(r),
+            srgb_decode(g),
+            srgb_decode(b),
+            from_u8(hsva.a),
+        )
+    }
+}
+
+impl From<LinRgba> for Hsva {
+    fn from(lin: LinRgba) -> Self {
+        let (h, s, v) = hsv_from_srgb(srgb_encode(lin.r), srgb_encode(lin.g), srgb_encode(lin.b));
+        Self::new(h, s, v, to_u8(lin.alpha))
+    }
+}
+
+impl From<Hsla> for LinRgba {
+    fn from(hsla: Hsla) -> Self {
+        let (r, g, b) = srgb_from_hsl(hsla.h, hsla.s, hsla.l);
+        Self::new(
+            srgb_decode(r),
+            srgb_decode(g),
+            srgb_decode(b),
+            from_u8(hsla.a),
+        )
+    }
+}
+
+impl From<LinRgba> for Hsla {
+    fn from(lin: LinRgba) -> Self {
+        let (h, s, l) = hsl_from_srgb(srgb_encode(lin.r), srgb_encode(lin.g), srgb_encode(lin.b));
+        Self::new(h, s, l, to_u8(lin.alpha))
+    }
+}
+
+// Hwb is Hsv with the saturation and value mixed differently, the conversions share its helpers
+// instead of round tripping through Hsva and its 8-bit alpha
+impl From<Hsva> for Hwb {
+    fn from(hsva: Hsva) -> Self {
+        Self::new(
+            hsva.h,
+            (1.0 - hsva.s) * hsva.v,
+            1.0 - hsva.v,
+            from_u8(hsva.a),
+        )
+    }
+}
+
+impl From<Hwb> for Hsva {
+    fn from(hwb: Hwb) -> Self {
+        let (h, s, v) = hsv_from_hwb(hwb.h, hwb.w, hwb.b);
+        Self::new(h, s, v, to_u8(hwb.alpha))
+    }
+}
+
+impl From<Hwb> for LinRgba {
+    fn from(hwb: Hwb) -> Self {
+        let (h, s, v) = hsv_from_hwb(hwb.h, hwb.w, hwb.b);
+        let (r, g, b) = srgb_from_hsv(h, s, v);
+        Self::new(srgb_decode(r), srgb_decode(g), srgb_decode(b), hwb.alpha)
+    }
+}
+
+impl From<LinRgba> for Hwb {
+    fn from(lin: LinRgba) -> Self {
+        let (h, s, v) = hsv_from_srgb(srgb_encode(lin.r), srgb_encode(lin.g), srgb_encode(lin.b));
+        Self::new(h, (1.0 - s) * v, 1.0 - v, lin.alpha)
+    }
+}
+
+impl From<LinRgba> for Xyz {
+    fn from(lin: LinRgba) -> Self {
+        let (x, y, z) = mul3(&XYZ_FROM_LIN_RGB, (lin.r, lin.g, lin.b));
+        Self::new(x, y, z, lin.alpha)
+    }
+}
+
+impl From<Xyz> for LinRgba {
+    fn from(xyz: Xyz) -> Self {
+        let (r, g, b) = mul3(&LIN_RGB_FROM_XYZ, (xyz.x, xyz.y, xyz.z));
+        Self::new(r, g, b, xyz.alpha)
+    }
+}
+
+impl From<Xyz> for Lab {
+    fn from(xyz: Xyz) -> Self {
+        let (wx, wy, wz) = D65_WHITE;
+        let fx = lab_f(xyz.x / wx);
+        let fy = lab_f(xyz.y / wy);
+        let fz = lab_f(xyz.z / wz);
+
+        Self::new(
+            116.0 * fy - 16.0,
+            500.0 * (fx - fy),
+            200.0 * (fy - fz),
+            xyz.alpha,
+        )
+    }
+}
+
+impl From<Lab> for Xyz {
+    fn from(lab: Lab) -> Self {
+        let (wx, wy, wz) = D65_WHITE;
+        let fy = (lab.l + 16.0) / 116.0;
+        let fx = fy + lab.a / 500.0;
+        let fz = fy - lab.b / 200.0;
+
+        Self::new(
+            wx * lab_f_inv(fx),
+            wy * lab_f_inv(fy),
+            wz * lab_f_inv(fz),
+            lab.alpha,
+        )
+    }
+}
+
+impl From<Lab> for Lch {
+    fn from(lab: Lab) -> Self {
+        let (c, h) = polar(lab.a, lab.b);
+        Self::new(lab.l, c, h, lab.alpha)
+    }
+}
+
+impl From<Lch> for Lab {
+    fn from(lch: Lch) -> Self {
+        let (a, b) = cartesian(lch.c, lch.h);
+        Self::new(lch.l, a, b, lch.alpha)
+    }
+}
+
+impl From<LinRgba> for Oklab {
+    fn from(lin: LinRgba) -> Self {
+        let (l, m, s) = mul3(&LMS_FROM_LIN_RGB, (lin.r, lin.g, lin.b));
+        let (l, a, b) = mul3(&OKLAB_FROM_LMS, (l.cbrt(), m.cbrt(), s.cbrt()));
+        Self::new(l, a, b, lin.alpha)
+    }
+}
+
+impl From<Oklab> for LinRgba {
+    fn from(oklab: Oklab) -> Self {
+        let (l, m, s) = mul3(&LMS_FROM_OKLAB, (oklab.l, oklab.a, oklab.b));
+        let (r, g, b) = mul3(&LIN_RGB_FROM_LMS, (l * l * l, m * m * m, s * s * s));
+        Self::new(r, g, b, oklab.alpha)
+    }
+}
+
+impl From<Oklab> for Oklch {
+    fn from(oklab: Oklab) -> Self {
+        let (c, h) = polar(oklab.a, oklab.b);
+        Self::new(oklab.l, c, h, oklab.alpha)
+    }
+}
+
+impl From<Oklch> for Oklab {
+    fn from(oklch: Oklch) -> Self {
+        let (a, b) = cartesian(oklch.c, oklch.h);
+        Self::new(oklch.l, a, b, oklch.alpha)
+    }
+}
+
+// From impls that chain through the space in front of the colon
+macro_rules! color_from_via {
+    ($via:ty: $($from:ty => [$($to:ty),*]);* $(;)?) => {
+        $($(
+            impl From<$from> for $to {
+                fn from(color: $from) -> Self {
+                    Self::from(<$via>::from(color))
+                }
+            }
+        )*)*
+    };
+}
+
+color_from_via!(Xyz: LinRgba => [Lab]; Lab => [LinRgba]);
+color_from_via!(Lab: LinRgba => [Lch]; Lch => [LinRgba]);
+color_from_via!(Oklab: LinRgba => [Oklch]; Oklch => [LinRgba]);
+color_from_via!(LinRgba:
+    Rgba => [Hwb, Xyz, Lab, Lch, Oklab, Oklch];
+    Hsva => [Xyz, Lab, Lch, Oklab, Oklch];
+    Hsla => [Hwb, Xyz, Lab, Lch, Oklab, Oklch];
+    Hwb => [Rgba, Hsla, Xyz, Lab, Lch, Oklab, Oklch];
+    Xyz => [Rgba, Hsva, Hsla, Hwb, Lch, Oklab, Oklch];
+    Lab => [Rgba, Hsva, Hsla, Hwb, Oklab, Oklch];
+    Lch => [Rgba, Hsva, Hsla, Hwb, Xyz, Oklab, Oklch];
+    Oklab => [Rgba, Hsva, Hsla, Hwb, Xyz, Lab, Lch];
+    Oklch => [Rgba, Hsva, Hsla, Hwb, Xyz, Lab, Lch];
+);
+
 pub trait GlColor {
     fn gl_color(&self) -> (f32, f32, f32, f32);
 }
@@ -161,10 +468,187 @@ impl GlColor for Hsla {
     }
 }
 
-fn rgb_from_hcm(h: f32, c: f32, m: f32) -> (u8, u8, u8) {
+// The framebuffer isn't sRGB, so GL wants sRGB encoded values like Rgba gives it
+impl GlColor for LinRgba {
+    fn gl_color(&self) -> (f32, f32, f32, f32) {
+        (
+            srgb_encode(self.r).clamp(0.0, 1.0),
+            srgb_encode(self.g).clamp(0.0, 1.0),
+            srgb_encode(self.b).clamp(0.0, 1.0),
+            self.alpha.clamp(0.0, 1.0),
+        )
+    }
+}
+
+macro_rules! gl_color_via_lin_rgba {
+    ($($t:ty),*) => {
+        $(
+            impl GlColor for $t {
+                fn gl_color(&self) -> (f32, f32, f32, f32) {
+                    LinRgba::from(*self).gl_color()
+                }
+            }
+        )*
+    };
+}
+
+gl_color_via_lin_rgba!(Xyz, Lab, Lch, Oklab, Oklch, Hwb);
+
+// sRGB D65 primaries
+const XYZ_FROM_LIN_RGB: [[f32; 3]; 3] = [
+    [0.412_390_8, 0.357_584_33, 0.180_480_8],
+    [0.212_639, 0.715_168_65, 0.072_192_32],
+    [0.019_330_818, 0.119_194_78, 0.950_532_14],
+];
+const LIN_RGB_FROM_XYZ: [[f32; 3]; 3] = [
+    [3.240_97, -1.537_383_2, -0.498_610_76],
+    [-0.969_243_6, 1.875_967_5, 0.041_555_06],
+    [0.055_630_08, -0.203_976_96, 1.056_971_5],
+];
+// XYZ of linear (1, 1, 1), so white comes out of Lab with a and b at 0
+const D65_WHITE: (f32, f32, f32) = (0.950_455_9, 1.0, 1.089_057_7);
+
+// From https://bottosson.github.io/posts/oklab/
+const LMS_FROM_LIN_RGB: [[f32; 3]; 3] = [
+    [0.412_221_46, 0.536_332_55, 0.051_445_995],
+    [0.211_903_5, 0.680_699_5, 0.107_396_96],
+    [0.088_302_46, 0.281_718_85, 0.629_978_7],
+];
+const OKLAB_FROM_LMS: [[f32; 3]; 3] = [
+    [0.210_454_26, 0.793_617_8, -0.004_072_047],
+    [1.977_998_5, -2.428_592_2, 0.450_593_7],
+    [0.025_904_037, 0.782_771_77, -0.808_675_77],
+];
+const LMS_FROM_OKLAB: [[f32; 3]; 3] = [
+    [1.0, 0.396_337_78, 0.215_803_76],
+    [1.0, -0.105_561_346, -0.063_854_17],
+    [1.0, -0.089_484_18, -1.291_485_5],
+];
+const LIN_RGB_FROM_LMS: [[f32; 3]; 3] = [
+    [4.076_741_7, -3.307_711_6, 0.230_969_94],
+    [-1.268_438, 2.609_757_4, -0.341_319_38],
+    [-0.004_196_086_3, -0.703_418_6, 1.707_614_7],
+];
+
+fn mul3(m: &[[f32; 3]; 3], (x, y, z): (f32, f32, f32)) -> (f32, f32, f32) {
+    (
+        m[0][0] * x + m[0][1] * y + m[0][2] * z,
+        m[1][0] * x + m[1][1] * y + m[1][2] * z,
+        m[2][0] * x + m[2][1] * y + m[2][2] * z,
+    )
+}
+
+fn from_u8(v: u8) -> f32 {
+    v as f32 / 255.0
+}
+
+fn to_u8(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+// Mirrored for negative values so out of gamut colors survive a round trip
+fn srgb_decode(v: f32) -> f32 {
+    let x = v.abs();
+    let lin = if x <= 0.040_45 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    };
+    lin.copysign(v)
+}
+
+fn srgb_encode(v: f32) -> f32 {
+    let x = v.abs();
+    let srgb = if x <= 0.003_130_8 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    };
+    srgb.copysign(v)
+}
+
+const LAB_DELTA: f32 = 6.0 / 29.0;
+
+fn lab_f(t: f32) -> f32 {
+    if t > LAB_DELTA * LAB_DELTA * LAB_DELTA {
+        t.cbrt()
+    } else {
+        t / (3.0 * LAB_DELTA * LAB_DELTA) + 4.0 / 29.0
+    }
+}
+
+fn lab_f_inv(t: f32) -> f32 {
+    if t > LAB_DELTA {
+        t * t * t
+    } else {
+        3.0 * LAB_DELTA * LAB_DELTA * (t - 4.0 / 29.0)
+    }
+}
+
+// Chroma below this is rounding noise on a gray (around 3e-5 in Lab and 3e-7 in Oklab for
+// sRGB grays) and far too small to see in either space
+const ACHROMATIC_CHROMA: f32 = 1e-4;
+
+// (chroma, hue in degrees) from the two opponent axes. Grays have no hue, they get 0 for both
+// rather than whatever angle the noise points at.
+fn polar(a: f32, b: f32) -> (f32, f32) {
+    let c = a.hypot(b);
+    if c < ACHROMATIC_CHROMA {
+        (0.0, 0.0)
+    } else {
+        (c, b.atan2(a).to_degrees().rem_euclid(360.0))
+    }
+}
+
+fn cartesian(c: f32, h: f32) -> (f32, f32) {
+    let (sin, cos) = h.to_radians().sin_cos();
+    (c * cos, c * sin)
+}
+
+fn srgb_from_hsv(h: f32, s: f32, v: f32) -> (f32, f32, f32) {
+    let c = v * s;
+    srgb_from_hcm(h, c, v - c)
+}
+
+fn srgb_from_hsl(h: f32, s: f32, l: f32) -> (f32, f32, f32) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    srgb_from_hcm(h, c, l - (c / 2.0))
+}
+
+fn hsv_from_srgb(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let (h, v, c) = hue_from_rgb(r, g, b);
+    let s = if v == 0.0 { 0.0 } else { c / v };
+    (h, s, v)
+}
+
+fn hsl_from_srgb(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let (h, v, c) = hue_from_rgb(r, g, b);
+    let l = v - c / 2.0;
+    let s = if l == 0.0 || l == 1.0 {
+        0.0
+    } else {
+        c / (1.0 - (2.0 * v - c - 1.0).abs())
+    };
+    (h, s, l)
+}
+
+// Whiteness and blackness adding up to more than 1 is a gray, they're scaled down to fit
+fn hsv_from_hwb(h: f32, w: f32, b: f32) -> (f32, f32, f32) {
+    let (w, b) = if w + b > 1.0 {
+        (w / (w + b), b / (w + b))
+    } else {
+        (w, b)
+    };
+    let v = 1.0 - b;
+    let s = if v == 0.0 { 0.0 } else { 1.0 - w / v };
+    (h, s, v)
+}
+
+fn srgb_from_hcm(h: f32, c: f32, m: f32) -> (f32, f32, f32) {
+    let h = h.rem_euclid(360.0);
     let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
 
-    let (r_1, b_1, g_1) = if h < 60.0 {
+    let (r_1, g_1, b_1) = if h < 60.0 {
         (c, x, 0.0)
     } else if h < 120.0 {
         (x, c, 0.0)
@@ -174,17 +658,11 @@ fn rgb_from_hcm(h: f32, c: f32, m: f32) -> (u8, u8, u8) {
         (0.0, x, c)
     } else if h < 300.0 {
         (x, 0.0, c)
-    } else if h < 360.0 {
-        (c, 0.0, x)
     } else {
-        unreachable!()
+        (c, 0.0, x)
     };
 
-    (
-        ((r_1 + m) * 255.0).round() as u8,
-        ((g_1 + m) * 255.0).round() as u8,
-        ((b_1 + m) * 255.0).round() as u8,
-    )
+    (r_1 + m, g_1 + m, b_1 + m)
 }
 
 fn hue_from_rgb(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
@@ -196,7 +674,7 @@ fn hue_from_rgb(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
         60.0 * if c == 0.0 {
             0.0
         } else if x_max == r {
-            ((g - b) / c) % 6.0
+            ((g - b) / c).rem_euclid(6.0)
         } else if x_max == g {
             ((b - r) / c) + 2.0
         } else if x_max == b {
@@ -279,4 +757,162 @@ mod test {
         assert_relative_eq!(hsv.v, 0.87, epsilon = 0.01);
         assert_eq!(hsv.a, hsl.a);
     }
+
+    #[test]
+    fn hsv_to_rgb_all_sectors() {
+        let hues = [0.0, 30.0, 90.0, 150.0, 210.0, 270.0, 330.0, 360.0, -30.0];
+        let rgbs = [
+            0xff0000ff, 0xff8000ff, 0x80ff00ff, 0x00ff80ff, 0x0080ffff, 0x8000ffff, 0xff0080ff,
+            0xff0000ff, 0xff0080ff,
+        ];
+        for (h, rgb) in hues.into_iter().zip(rgbs) {
+            assert_eq!(Rgba::from(Hsva::new(h, 1.0, 1.0, 255)), Rgba::hex(rgb));
+        }
+
+        let hsv = Hsva::from(Rgba::hex(0xff00ffff));
+        assert_relative_eq!(hsv.h, 300.0, epsilon = 0.01);
+    }
+
+    #[test]
+    fn rgb_to_lin_rgb() {
+        let lin = LinRgba::from(Rgba::hex(0x80ff0080));
+
+        assert_relative_eq!(lin.r, 0.2158, epsilon = 0.0001);
+        assert_relative_eq!(lin.g, 1.0, epsilon = 0.0001);
+        assert_relative_eq!(lin.b, 0.0, epsilon = 0.0001);
+        assert_relative_eq!(lin.alpha, 0.5, epsilon = 0.01);
+        assert_eq!(Rgba::from(lin), Rgba::hex(0x80ff0080));
+    }
+
+    #[test]
+    fn rgb_to_xyz() {
+        let white = Xyz::from(Rgba::hex(0xffffffff));
+
+        assert_relative_eq!(white.x, 0.9505, epsilon = 0.001);
+        assert_relative_eq!(white.y, 1.0, epsilon = 0.001);
+        assert_relative_eq!(white.z, 1.0891, epsilon = 0.001);
+
+        let red = Xyz::from(Rgba::hex(0xff0000ff));
+
+        assert_relative_eq!(red.x, 0.4124, epsilon = 0.001);
+        assert_relative_eq!(red.y, 0.2126, epsilon = 0.001);
+        assert_relative_eq!(red.z, 0.0193, epsilon = 0.001);
+    }
+
+    #[test]
+    fn rgb_to_lab() {
+        let lab = Lab::from(Rgba::hex(0xff0000ff));
+
+        assert_relative_eq!(lab.l, 53.24, epsilon = 0.01);
+        assert_relative_eq!(lab.a, 80.09, epsilon = 0.01);
+        assert_relative_eq!(lab.b, 67.20, epsilon = 0.01);
+        assert_eq!(lab.alpha, 1.0);
+
+        let lch = Lch::from(lab);
+
+        assert_relative_eq!(lch.l, 53.24, epsilon = 0.01);
+        assert_relative_eq!(lch.c, 104.55, epsilon = 0.01);
+        assert_relative_eq!(lch.h, 40.0, epsilon = 0.01);
+
+        let white = Lab::from(Rgba::hex(0xffffffff));
+
+        assert_relative_eq!(white.l, 100.0, epsilon = 0.001);
+        assert_relative_eq!(white.a, 0.0, epsilon = 0.001);
+        assert_relative_eq!(white.b, 0.0, epsilon = 0.001);
+    }
+
+    #[test]
+    fn rgb_to_oklab() {
+        let oklab = Oklab::from(Rgba::hex(0xff0000ff));
+
+        assert_relative_eq!(oklab.l, 0.6280, epsilon = 0.0001);
+        assert_relative_eq!(oklab.a, 0.2249, epsilon = 0.0001);
+        assert_relative_eq!(oklab.b, 0.1258, epsilon = 0.0001);
+
+        let oklch = Oklch::from(oklab);
+
+        assert_relative_eq!(oklch.l, 0.6280, epsilon = 0.0001);
+        assert_relative_eq!(oklch.c, 0.2577, epsilon = 0.0001);
+        assert_relative_eq!(oklch.h, 29.23, epsilon = 0.01);
+
+        let white = Oklab::from(Rgba::hex(0xffffffff));
+
+        assert_relative_eq!(white.l, 1.0, epsilon = 0.0001);
+        assert_relative_eq!(white.a, 0.0, epsilon = 0.0001);
+        assert_relative_eq!(white.b, 0.0, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn grays_have_no_hue() {
+        for v in (0..=255).step_by(15) {
+            let gray = Rgba::new(v, v, v, 255);
+
+            let oklch = Oklch::from(gray);
+            assert_eq!((oklch.c, oklch.h), (0.0, 0.0));
+            let lch = Lch::from(gray);
+            assert_eq!((lch.c, lch.h), (0.0, 0.0));
+
+            assert_eq!(Rgba::from(oklch), gray);
+        }
+    }
+
+    #[test]
+    fn rgb_to_hwb() {
+        let hwb = Hwb::from(Rgba::hex(0x60bfbfff));
+
+        assert_relative_eq!(hwb.h, 180.0, epsilon = 0.01);
+        assert_relative_eq!(hwb.w, 0.375, epsilon = 0.01);
+        assert_relative_eq!(hwb.b, 0.25, epsilon = 0.01);
+
+        // Whiteness and blackness over 1 is a gray
+        assert_eq!(
+            Rgba::from(Hwb::new(0.0, 0.6, 0.6, 1.0)),
+            Rgba::hex(0x808080ff)
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let colors = [
+            0x000000ff, 0xffffffff, 0x808080ff, 0xff0000ff, 0x00ff0080, 0x0000ff00, 0x60bfbfff,
+            0x9fdfdfff, 0xff8000ff, 0x123456ff, 0xfedcbaff, 0x01fe7fff,
+        ];
+        for rgb in colors.map(Rgba::hex) {
+            assert_eq!(Rgba::from(LinRgba::from(rgb)), rgb);
+            assert_eq!(Rgba::from(Xyz::from(rgb)), rgb);
+            assert_eq!(Rgba::from(Lab::from(rgb)), rgb);
+            assert_eq!(Rgba::from(Lch::from(rgb)), rgb);
+            assert_eq!(Rgba::from(Oklab::from(rgb)), rgb);
+            assert_eq!(Rgba::from(Oklch::from(rgb)), rgb);
+            assert_eq!(Rgba::from(Hwb::from(rgb)), rgb);
+            assert_eq!(Rgba::from(Hsva::from(rgb)), rgb);
+            assert_eq!(Rgba::from(Hsla::from(rgb)), rgb);
+        }
+
+        // Out of gamut colors survive the float spaces
+        let lin = LinRgba::new(1.2, -0.1, 0.5, 0.75);
+        let back = LinRgba::from(Oklch::from(Lch::from(lin)));
+
+        assert_relative_eq!(back.r, lin.r, epsilon = 0.0001);
+        assert_relative_eq!(back.g, lin.g, epsilon = 0.0001);
+        assert_relative_eq!(back.b, lin.b, epsilon = 0.0001);
+        assert_eq!(back.alpha, lin.alpha);
+    }
+
+    #[test]
+    fn gl_colors_match() {
+        let rgb = Rgba::hex(0x60bfbf80);
+        let (r, g, b, a) = rgb.gl_color();
+
+        for color in [
+            Oklch::from(rgb).gl_color(),
+            Lab::from(rgb).gl_color(),
+            Hwb::from(rgb).gl_color(),
+        ] {
+            assert_relative_eq!(color.0, r, epsilon = 0.001);
+            assert_relative_eq!(color.1, g, epsilon = 0.001);
+            assert_relative_eq!(color.2, b, epsilon = 0.001);
+            assert_relative_eq!(color.3, a, epsilon = 0.001);
+        }
+    }
 }
@@ -0,0 +1,575 @@
+use super::{
+    Hsla, Hsva, Hwb, Lab, Lch, LinRgba, Oklab, Oklch, Rgba, Xyz, from_u8, hsl_from_srgb,
+    hsv_from_srgb, srgb_decode, srgb_from_hsl, srgb_from_hsv, to_u8,
+};
+use crate::{Error, Result};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// A parsed CSS color in the space it was written in, so "hsl(...)" parsed as Hsla comes out exact
+enum CssColor {
+    // sRGB, all 0-1
+    Rgb(f32, f32, f32, f32),
+    Hsl(f32, f32, f32, f32),
+    Hsv(f32, f32, f32, f32),
+}
+
+// Parses "#rgb", "#rgba", "#rrggbb", "#rrggbbaa", rgb()/rgba(), hsl()/hsla(), hsv()/hsva() and the
+// CSS named colors. Functions take either the comma separated form ("rgb(255, 0, 0, 0.5)") or the
+// space separated one ("hsl(120deg 50% 50% / 50%)").
+fn parse(s: &str) -> Result<CssColor> {
+    let s = s.trim();
+
+    if let Some(digits) = s.strip_prefix('#') {
+        return parse_hex(digits)
+            .ok_or_else(|| Error::ColorParse(format!("invalid hex color '{s}'")));
+    }
+
+    if let Some((function, args)) = s.strip_suffix(')').and_then(|s| s.split_once('(')) {
+        let function = function.trim().to_ascii_lowercase();
+        let invalid = || Error::ColorParse(format!("invalid arguments in '{s}'"));
+        let ([x, y, z], alpha) = split_args(args).ok_or_else(invalid)?;
+        let alpha = match alpha {
+            Some(alpha) => percentage_or(alpha, 1.0).ok_or_else(invalid)?,
+            None => 1.0,
+        };
+
+        return match function.as_str() {
+            "rgb" | "rgba" => {
+                let channel = |v| percentage_or(v, 255.0).ok_or_else(invalid);
+                Ok(CssColor::Rgb(channel(x)?, channel(y)?, channel(z)?, alpha))
+            }
+            "hsl" | "hsla" | "hsv" | "hsva" => {
+                let hue = angle(x).ok_or_else(invalid)?;
+                let fraction = |v| percentage_or(v, 100.0).ok_or_else(invalid);
+                let (y, z) = (fraction(y)?, fraction(z)?);
+                if function.starts_with("hsl") {
+                    Ok(CssColor::Hsl(hue, y, z, alpha))
+                } else {
+                    Ok(CssColor::Hsv(hue, y, z, alpha))
+                }
+            }
+            _ => Err(Error::ColorParse(format!(
+                "unknown color function '{function}' in '{s}'"
+            ))),
+        };
+    }
+
+    if s.eq_ignore_ascii_case("transparent") {
+        return Ok(CssColor::Rgb(0.0, 0.0, 0.0, 0.0));
+    }
+
+    NAMED_COLORS
+        .iter()
+        .find(|(_, name)| name.eq_ignore_ascii_case(s))
+        .map(|(rgb, _)| {
+            let [_, r, g, b] = rgb.to_be_bytes();
+            CssColor::Rgb(from_u8(r), from_u8(g), from_u8(b), 1.0)
+        })
+        .ok_or_else(|| Error::ColorParse(format!("unknown color '{s}'")))
+}
+
+fn parse_hex(digits: &str) -> Option<CssColor> {
+    if !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let v = u32::from_str_radix(digits, 16).ok()?;
+
+    // Short forms repeat each digit, "#f80" is "#ff8800"
+    let [r, g, b, a] = match digits.len() {
+        3 | 4 => {
+            let n = digits.len() as u32;
+            let nibble = |i: u32| ((v >> (4 * (n - 1 - i))) & 0xf) as u8 * 0x11;
+            [
+                nibble(0),
+                nibble(1),
+                nibble(2),
+                if n == 4 { nibble(3) } else { 0xff },
+            ]
+        }
+        6 => (v << 8 | 0xff).to_be_bytes(),
+        8 => v.to_be_bytes(),
+        _ => return None,
+    };
+
+    Some(CssColor::Rgb(
+        from_u8(r),
+        from_u8(g),
+        from_u8(b),
+        from_u8(a),
+    ))
+}
+
+fn split_args(args: &str) -> Option<([&str; 3], Option<&str>)> {
+    let (components, alpha): (Vec<&str>, _) = if args.contains(',') {
+        let mut parts: Vec<&str> = args.split(',').map(str::trim).collect();
+        let alpha = if parts.len() == 4 { parts.pop() } else { None };
+        (parts, alpha)
+    } else {
+        let (components, alpha) = match args.split_once('/') {
+            Some((components, alpha)) => (components, Some(alpha.trim())),
+            None => (args, None),
+        };
+        (components.split_whitespace().collect(), alpha)
+    };
+
+    let components: [&str; 3] = components.try_into().ok()?;
+    Some((components, alpha))
+}
+
+fn number(s: &str) -> Option<f32> {
+    s.trim().parse::<f32>().ok().filter(|v| v.is_finite())
+}
+
+// "50%" or a plain number out of max, clamped to 0-1 like CSS does
+fn percentage_or(s: &str, max: f32) -> Option<f32> {
+    let v = match s.trim().strip_suffix('%') {
+        Some(percent) => number(percent)? / 100.0,
+        None => number(s)? / max,
+    };
+    Some(v.clamp(0.0, 1.0))
+}
+
+// Hue in degrees, plain numbers are degrees too
+fn angle(s: &str) -> Option<f32> {
+    let s = s.trim().to_ascii_lowercase();
+    let degrees = if let Some(v) = s.strip_suffix("deg") {
+        number(v)?
+    } else if let Some(v) = s.strip_suffix("grad") {
+        number(v)? * 0.9
+    } else if let Some(v) = s.strip_suffix("rad") {
+        number(v)?.to_degrees()
+    } else if let Some(v) = s.strip_suffix("turn") {
+        number(v)? * 360.0
+    } else {
+        number(&s)?
+    };
+    Some(degrees.rem_euclid(360.0))
+}
+
+impl FromStr for Rgba {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match parse(s)? {
+            CssColor::Rgb(r, g, b, a) => Self::new(to_u8(r), to_u8(g), to_u8(b), to_u8(a)),
+            CssColor::Hsl(h, s, l, a) => Self::from(Hsla::new(h, s, l, to_u8(a))),
+            CssColor::Hsv(h, s, v, a) => Self::from(Hsva::new(h, s, v, to_u8(a))),
+        })
+    }
+}
+
+impl FromStr for Hsva {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match parse(s)? {
+            CssColor::Rgb(r, g, b, a) => {
+                let (h, s, v) = hsv_from_srgb(r, g, b);
+                Self::new(h, s, v, to_u8(a))
+            }
+            CssColor::Hsl(h, s, l, a) => Self::from(Hsla::new(h, s, l, to_u8(a))),
+            CssColor::Hsv(h, s, v, a) => Self::new(h, s, v, to_u8(a)),
+        })
+    }
+}
+
+impl FromStr for Hsla {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match parse(s)? {
+            CssColor::Rgb(r, g, b, a) => {
+                let (h, s, l) = hsl_from_srgb(r, g, b);
+                Self::new(h, s, l, to_u8(a))
+            }
+            CssColor::Hsl(h, s, l, a) => Self::new(h, s, l, to_u8(a)),
+            CssColor::Hsv(h, s, v, a) => Self::from(Hsva::new(h, s, v, to_u8(a))),
+        })
+    }
+}
+
+impl FromStr for LinRgba {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (r, g, b, alpha) = match parse(s)? {
+            CssColor::Rgb(r, g, b, a) => (r, g, b, a),
+            CssColor::Hsl(h, s, l, a) => {
+                let (r, g, b) = srgb_from_hsl(h, s, l);
+                (r, g, b, a)
+            }
+            CssColor::Hsv(h, s, v, a) => {
+                let (r, g, b) = srgb_from_hsv(h, s, v);
+                (r, g, b, a)
+            }
+        };
+        Ok(Self::new(
+            srgb_decode(r),
+            srgb_decode(g),
+            srgb_decode(b),
+            alpha,
+        ))
+    }
+}
+
+macro_rules! from_str_via_lin_rgba {
+    ($($t:ty),*) => {
+        $(
+            impl FromStr for $t {
+                type Err = Error;
+
+                fn from_str(s: &str) -> Result<Self> {
+                    Ok(Self::from(s.parse::<LinRgba>()?))
+                }
+            }
+        )*
+    };
+}
+
+from_str_via_lin_rgba!(Xyz, Lab, Lch, Oklab, Oklch, Hwb);
+
+// "#rrggbb", or "#rrggbbaa" when it isn't opaque
+impl Display for Rgba {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if self.a != 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+// "hsv(180 50% 75%)", with " / 0.5" on the end when it isn't opaque. Not CSS, but parses back.
+impl Display for Hsva {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_function(f, "hsv", self.h, self.s, self.v, self.a)
+    }
+}
+
+// "hsl(180 50% 75%)", with " / 0.5" on the end when it isn't opaque
+impl Display for Hsla {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_function(f, "hsl", self.h, self.s, self.l, self.a)
+    }
+}
+
+fn write_function(
+    f: &mut Formatter<'_>,
+    name: &str,
+    h: f32,
+    x: f32,
+    y: f32,
+    alpha: u8,
+) -> std::fmt::Result {
+    // Two decimals are plenty and keep 8-bit colors from printing as 74.90196%. Alpha is a 0-1
+    // fraction, so it needs a third to tell all 256 values apart.
+    let round = |v: f32, scale: f32| (v * scale).round() / scale + 0.0;
+    write!(
+        f,
+        "{name}({} {}% {}%",
+        round(h, 100.0),
+        round(x * 100.0, 100.0),
+        round(y * 100.0, 100.0)
+    )?;
+    if alpha != 255 {
+        write!(f, " / {}", round(from_u8(alpha), 1000.0))?;
+    }
+    write!(f, ")")
+}
+
+const NAMED_COLORS: [(u32, &str); 148] = [
+    (0xf0f8ff, "aliceblue"),
+    (0xfaebd7, "antiquewhite"),
+    (0x00ffff, "aqua"),
+    (0x7fffd4, "aquamarine"),
+    (0xf0ffff, "azure"),
+    (0xf5f5dc, "beige"),
+    (0xffe4c4, "bisque"),
+    (0x000000, "black"),
+    (0xffebcd, "blanchedalmond"),
+    (0x0000ff, "blue"),
+    (0x8a2be2, "blueviolet"),
+    (0xa52a2a, "brown"),
+    (0xdeb887, "burlywood"),
+    (0x5f9ea0, "cadetblue"),
+    (0x7fff00, "chartreuse"),
+    (0xd2691e, "chocolate"),
+    (0xff7f50, "coral"),
+    (0x6495ed, "cornflowerblue"),
+    (0xfff8dc, "cornsilk"),
+    (0xdc143c, "crimson"),
+    (0x00ffff, "cyan"),
+    (0x00008b, "darkblue"),
+    (0x008b8b, "darkcyan"),
+    (0xb8860b, "darkgoldenrod"),
+    (0xa9a9a9, "darkgray"),
+    (0x006400, "darkgreen"),
+    (0xa9a9a9, "darkgrey"),
+    (0xbdb76b, "darkkhaki"),
+    (0x8b008b, "darkmagenta"),
+    (0x556b2f, "darkolivegreen"),
+    (0xff8c00, "darkorange"),
+    (0x9932cc, "darkorchid"),
+    (0x8b0000, "darkred"),
+    (0xe9967a, "darksalmon"),
+    (0x8fbc8f, "darkseagreen"),
+    (0x483d8b, "darkslateblue"),
+    (0x2f4f4f, "darkslategray"),
+    (0x2f4f4f, "darkslategrey"),
+    (0x00ced1, "darkturquoise"),
+    (0x9400d3, "darkviolet"),
+    (0xff1493, "deeppink"),
+    (0x00bfff, "deepskyblue"),
+    (0x696969, "dimgray"),
+    (0x696969, "dimgrey"),
+    (0x1e90ff, "dodgerblue"),
+    (0xb22222, "firebrick"),
+    (0xfffaf0, "floralwhite"),
+    (0x228b22, "forestgreen"),
+    (0xff00ff, "fuchsia"),
+    (0xdcdcdc, "gainsboro"),
+    (0xf8f8ff, "ghostwhite"),
+    (0xffd700, "gold"),
+    (0xdaa520, "goldenrod"),
+    (0x808080, "gray"),
+    (0x008000, "green"),
+    (0xadff2f, "greenyellow"),
+    (0x808080, "grey"),
+    (0xf0fff0, "honeydew"),
+    (0xff69b4, "hotpink"),
+    (0xcd5c5c, "indianred"),
+    (0x4b0082, "indigo"),
+    (0xfffff0, "ivory"),
+    (0xf0e68c, "khaki"),
+    (0xe6e6fa, "lavender"),
+    (0xfff0f5, "lavenderblush"),
+    (0x7cfc00, "lawngreen"),
+    (0xfffacd, "lemonchiffon"),
+    (0xadd8e6, "lightblue"),
+    (0xf08080, "lightcoral"),
+    (0xe0ffff, "lightcyan"),
+    (0xfafad2, "lightgoldenrodyellow"),
+    (0xd3d3d3, "lightgray"),
+    (0x90ee90, "lightgreen"),
+    (0xd3d3d3, "lightgrey"),
+    (0xffb6c1, "lightpink"),
+    (0xffa07a, "lightsalmon"),
+    (0x20b2aa, "lightseagreen"),
+    (0x87cefa, "lightskyblue"),
+    (0x778899, "lightslategray"),
+    (0x778899, "lightslategrey"),
+    (0xb0c4de, "lightsteelblue"),
+    (0xffffe0, "lightyellow"),
+    (0x00ff00, "lime"),
+    (0x32cd32, "limegreen"),
+    (0xfaf0e6, "linen"),
+    (0xff00ff, "magenta"),
+    (0x800000, "maroon"),
+    (0x66cdaa, "mediumaquamarine"),
+    (0x0000cd, "mediumblue"),
+    (0xba55d3, "mediumorchid"),
+    (0x9370db, "mediumpurple"),
+    (0x3cb371, "mediumseagreen"),
+    (0x7b68ee, "mediumslateblue"),
+    (0x00fa9a, "mediumspringgreen"),
+    (0x48d1cc, "mediumturquoise"),
+    (0xc71585, "mediumvioletred"),
+    (0x191970, "midnightblue"),
+    (0xf5fffa, "mintcream"),
+    (0xffe4e1, "mistyrose"),
+    (0xffe4b5, "moccasin"),
+    (0xffdead, "navajowhite"),
+    (0x000080, "navy"),
+    (0xfdf5e6, "oldlace"),
+    (0x808000, "olive"),
+    (0x6b8e23, "olivedrab"),
+    (0xffa500, "orange"),
+    (0xff4500, "orangered"),
+    (0xda70d6, "orchid"),
+    (0xeee8aa, "palegoldenrod"),
+    (0x98fb98, "palegreen"),
+    (0xafeeee, "paleturquoise"),
+    (0xdb7093, "palevioletred"),
+    (0xffefd5, "papayawhip"),
+    (0xffdab9, "peachpuff"),
+    (0xcd853f, "peru"),
+    (0xffc0cb, "pink"),
+    (0xdda0dd, "plum"),
+    (0xb0e0e6, "powderblue"),
+    (0x800080, "purple"),
+    (0x663399, "rebeccapurple"),
+    (0xff0000, "red"),
+    (0xbc8f8f, "rosybrown"),
+    (0x4169e1, "royalblue"),
+    (0x8b4513, "saddlebrown"),
+    (0xfa8072, "salmon"),
+    (0xf4a460, "sandybrown"),
+    (0x2e8b57, "seagreen"),
+    (0xfff5ee, "seashell"),
+    (0xa0522d, "sienna"),
+    (0xc0c0c0, "silver"),
+    (0x87ceeb, "skyblue"),
+    (0x6a5acd, "slateblue"),
+    (0x708090, "slategray"),
+    (0x708090, "slategrey"),
+    (0xfffafa, "snow"),
+    (0x00ff7f, "springgreen"),
+    (0x4682b4, "steelblue"),
+    (0xd2b48c, "tan"),
+    (0x008080, "teal"),
+    (0xd8bfd8, "thistle"),
+    (0xff6347, "tomato"),
+    (0x40e0d0, "turquoise"),
+    (0xee82ee, "violet"),
+    (0xf5deb3, "wheat"),
+    (0xffffff, "white"),
+    (0xf5f5f5, "whitesmoke"),
+    (0xffff00, "yellow"),
+    (0x9acd32, "yellowgreen"),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    #[test]
+    fn parse_hex() {
+        assert_eq!("#f80".parse::<Rgba>().unwrap(), Rgba::hex(0xff8800ff));
+        assert_eq!("#f808".parse::<Rgba>().unwrap(), Rgba::hex(0xff880088));
+        assert_eq!("#60BFBF".parse::<Rgba>().unwrap(), Rgba::hex(0x60bfbfff));
+        assert_eq!(
+            " #60bfbf80 ".parse::<Rgba>().unwrap(),
+            Rgba::hex(0x60bfbf80)
+        );
+
+        for bad in ["#", "#12", "#12345", "#1234567", "#ggg", "#+12", "60bfbf"] {
+            assert!(bad.parse::<Rgba>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn parse_rgb() {
+        let color = Rgba::hex(0xff800080);
+
+        assert_eq!("rgb(255, 128, 0, 0.5)".parse::<Rgba>().unwrap(), color);
+        assert_eq!("rgba(255,128,0,50%)".parse::<Rgba>().unwrap(), color);
+        assert_eq!("RGB(100% 50.2% 0 / 0.5)".parse::<Rgba>().unwrap(), color);
+        assert_eq!(
+            "rgb(300 128 -5 / 2)".parse::<Rgba>().unwrap(),
+            Rgba::hex(0xff8000ff)
+        );
+
+        for bad in [
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4, 5)",
+            "rgb(a, b, c)",
+            "rgb(1 2 3",
+            "rgbx(1 2 3)",
+        ] {
+            assert!(bad.parse::<Rgba>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn parse_hsl_and_hsv() {
+        let hsl = "hsl(180, 50%, 75%)".parse::<Hsla>().unwrap();
+        assert_eq!(hsl, Hsla::new(180.0, 0.5, 0.75, 255));
+        assert_eq!(hsl, "hsla(0.5turn 50 75 / 1)".parse::<Hsla>().unwrap());
+        assert_eq!(
+            "hsl(180, 50%, 75%)".parse::<Rgba>().unwrap(),
+            Rgba::hex(0x9fdfdfff)
+        );
+
+        let hsv = "hsv(-180deg 50% 75% / 50%)".parse::<Hsva>().unwrap();
+        assert_eq!(hsv, Hsva::new(180.0, 0.5, 0.75, 128));
+        assert_eq!(
+            "hsva(200grad 50% 75%)".parse::<Rgba>().unwrap(),
+            Rgba::hex(0x60bfbfff)
+        );
+
+        let hsv = "#60bfbf".parse::<Hsva>().unwrap();
+        assert_relative_eq!(hsv.h, 180.0, epsilon = 0.01);
+        assert_relative_eq!(hsv.s, 0.50, epsilon = 0.01);
+        assert_relative_eq!(hsv.v, 0.75, epsilon = 0.01);
+    }
+
+    #[test]
+    fn parse_named() {
+        assert_eq!("red".parse::<Rgba>().unwrap(), Rgba::hex(0xff0000ff));
+        assert_eq!(
+            "RebeccaPurple".parse::<Rgba>().unwrap(),
+            Rgba::hex(0x663399ff)
+        );
+        assert_eq!(
+            " grey ".parse::<Rgba>().unwrap(),
+            "gray".parse::<Rgba>().unwrap()
+        );
+        assert_eq!("transparent".parse::<Rgba>().unwrap(), Rgba::hex(0));
+        assert!("reddish".parse::<Rgba>().is_err());
+
+        for (i, (_, name)) in NAMED_COLORS.iter().enumerate() {
+            assert!(name.parse::<Rgba>().is_ok());
+            if i > 0 {
+                assert!(NAMED_COLORS[i - 1].1 < *name);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_float_spaces() {
+        let lin = "#808080".parse::<LinRgba>().unwrap();
+        assert_relative_eq!(lin.r, 0.2158, epsilon = 0.0001);
+
+        let oklch = "red".parse::<Oklch>().unwrap();
+        assert_relative_eq!(oklch.l, 0.6280, epsilon = 0.0001);
+        assert_relative_eq!(oklch.h, 29.23, epsilon = 0.01);
+
+        let hwb = "hsl(0 100% 50% / 0.25)".parse::<Hwb>().unwrap();
+        assert_relative_eq!(hwb.h, 0.0, epsilon = 0.01);
+        assert_relative_eq!(hwb.w, 0.0, epsilon = 0.0001);
+        assert_relative_eq!(hwb.b, 0.0, epsilon = 0.0001);
+        assert_eq!(hwb.alpha, 0.25);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Rgba::hex(0x60bfbfff).to_string(), "#60bfbf");
+        assert_eq!(Rgba::hex(0x60bfbf80).to_string(), "#60bfbf80");
+        assert_eq!(
+            Hsla::new(180.0, 0.5, 0.75, 255).to_string(),
+            "hsl(180 50% 75%)"
+        );
+        assert_eq!(
+            Hsva::from(Rgba::hex(0x60bfbf80)).to_string(),
+            "hsv(180 49.74% 74.9% / 0.502)"
+        );
+
+        for hex in [0x60bfbfff, 0x12345678, 0xff000000, 0x00ff80ff] {
+            let rgb = Rgba::hex(hex);
+            assert_eq!(rgb.to_string().parse::<Rgba>().unwrap(), rgb);
+            assert_eq!(
+                Rgba::from(Hsla::from(rgb).to_string().parse::<Hsla>().unwrap()),
+                rgb
+            );
+            assert_eq!(
+                Rgba::from(Hsva::from(rgb).to_string().parse::<Hsva>().unwrap()),
+                rgb
+            );
+            assert_eq!(rgb.to_hex(), hex);
+        }
+
+        for alpha in 0..=255 {
+            let rgb = Rgba::new(0x60, 0xbf, 0xbf, alpha);
+            assert_eq!(
+                Rgba::from(Hsla::from(rgb).to_string().parse::<Hsla>().unwrap()),
+                rgb
+            );
+            assert_eq!(
+                Rgba::from(Hsva::from(rgb).to_string().parse::<Hsva>().unwrap()),
+                rgb
+            );
+        }
+    }
+}
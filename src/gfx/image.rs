@@ -0,0 +1,135 @@
+use crate::{
+    Result,
+    gfx::Rgba,
+    gl,
+    gl::types::{GLint, GLsizei},
+    hlgl::Framebuffer,
+};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+// 8-bit RGBA pixels, rows stored top to bottom
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * 4,
+            "pixel data doesn't match image size"
+        );
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // Reads from the currently bound framebuffer, GL's bottom-left origin is flipped to top-left
+    pub fn read_gl_pixels(x: i32, y: i32, width: u32, height: u32) -> Self {
+        let mut pixels = vec![0u8; width as usize * height as usize * 4];
+        unsafe {
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                x as GLint,
+                y as GLint,
+                width as GLsizei,
+                height as GLsizei,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr().cast(),
+            );
+        }
+
+        let mut image = Self::new(width, height, pixels);
+        image.flip_vertical();
+        image
+    }
+
+    pub fn from_framebuffer(fb: &Framebuffer) -> Self {
+        let mut prev_fb: GLint = 0;
+        unsafe {
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut prev_fb);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, fb.id);
+        }
+
+        let (width, height) = fb.size();
+        let image = Self::read_gl_pixels(0, 0, width, height);
+
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, prev_fb as _);
+        }
+
+        image
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Rgba {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        Rgba::new(
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        )
+    }
+
+    pub fn flip_vertical(&mut self) {
+        let stride = self.width as usize * 4;
+        let height = self.height as usize;
+        for y in 0..height / 2 {
+            let (top, bottom) = self.pixels.split_at_mut((height - y - 1) * stride);
+            top[y * stride..(y + 1) * stride].swap_with_slice(&mut bottom[..stride]);
+        }
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn flip_vertical() {
+        #[rustfmt::skip]
+        let mut image = Image::new(1, 3, vec![
+            1, 1, 1, 1,
+            2, 2, 2, 2,
+            3, 3, 3, 3,
+        ]);
+        image.flip_vertical();
+
+        assert_eq!(image.pixel(0, 0), Rgba::new(3, 3, 3, 3));
+        assert_eq!(image.pixel(0, 1), Rgba::new(2, 2, 2, 2));
+        assert_eq!(image.pixel(0, 2), Rgba::new(1, 1, 1, 1));
+    }
+}
@@ -1,9 +1,13 @@
 mod buffer;
+mod framebuffer;
+mod query;
 mod shader;
 mod vec_buffer;
 mod vertex_array;
 
 pub use buffer::*;
+pub use framebuffer::*;
+pub use query::*;
 pub use shader::*;
 pub use vertex_array::*;
 
@@ -0,0 +1,99 @@
+use crate::{
+    Error, Result, gl,
+    gl::types::{GLenum, GLsizei, GLuint},
+};
+
+pub struct Framebuffer {
+    pub id: GLuint,
+    color_rb: GLuint,
+    depth_stencil_rb: GLuint,
+    width: u32,
+    height: u32,
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            log::trace!("Deleting framebuffer with id: {}", self.id);
+            gl::DeleteFramebuffers(1, &self.id);
+            gl::DeleteRenderbuffers(1, &self.color_rb);
+            gl::DeleteRenderbuffers(1, &self.depth_stencil_rb);
+            self.id = 0;
+        }
+    }
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        let mut fb = Self {
+            id: 0,
+            color_rb: 0,
+            depth_stencil_rb: 0,
+            width,
+            height,
+        };
+
+        let status = unsafe {
+            gl::GenFramebuffers(1, &mut fb.id);
+            log::trace!("Generated framebuffer with id: {}", fb.id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fb.id);
+
+            fb.color_rb = Self::attach_renderbuffer(
+                gl::RGBA8,
+                gl::COLOR_ATTACHMENT0,
+                width as GLsizei,
+                height as GLsizei,
+            );
+            fb.depth_stencil_rb = Self::attach_renderbuffer(
+                gl::DEPTH24_STENCIL8,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                width as GLsizei,
+                height as GLsizei,
+            );
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+
+        if status == gl::FRAMEBUFFER_COMPLETE {
+            Ok(fb)
+        } else {
+            Err(Error::FramebufferIncomplete(status))
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    unsafe fn attach_renderbuffer(
+        format: GLenum,
+        attachment: GLenum,
+        width: GLsizei,
+        height: GLsizei,
+    ) -> GLuint {
+        let mut id = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut id);
+            log::trace!("Generated renderbuffer with id: {}", id);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, id);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, format, width, height);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, attachment, gl::RENDERBUFFER, id);
+        }
+        id
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    gl,
+    gl::types::{GLint, GLuint},
+};
+use std::ffi::CStr;
+use std::time::Duration;
+
+// GL_TIME_ELAPSED query, measures how long the GPU spent on the commands between begin and end.
+// Only one can be active at a time, they don't nest. Needs GL 3.3 or ARB_timer_query, see
+// is_supported.
+pub struct TimerQuery {
+    pub id: GLuint,
+}
+
+impl Drop for TimerQuery {
+    fn drop(&mut self) {
+        unsafe {
+            log::trace!("Deleting query with id: {}", self.id);
+            gl::DeleteQueries(1, &self.id);
+            self.id = 0;
+        }
+    }
+}
+
+impl Default for TimerQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimerQuery {
+    // Whether the current context can run timer queries, check once after loading GL
+    pub fn is_supported() -> bool {
+        if !gl::GenQueries::is_loaded() || !gl::GetQueryObjectui64v::is_loaded() {
+            return false;
+        }
+
+        let (mut major, mut minor): (GLint, GLint) = (0, 0);
+        unsafe {
+            // Both are GL 3.0+, older contexts leave them at 0 and flag an error we clear
+            gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+            gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+            while gl::GetError() != gl::NO_ERROR {}
+        }
+        (major, minor) >= (3, 3) || has_extension(major, "GL_ARB_timer_query")
+    }
+
+    pub fn new() -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::GenQueries(1, &mut id);
+        }
+        log::trace!("Generated query with id: {}", id);
+        Self { id }
+    }
+
+    pub fn begin(&self) {
+        unsafe {
+            gl::BeginQuery(gl::TIME_ELAPSED, self.id);
+        }
+    }
+
+    pub fn end(&self) {
+        unsafe {
+            gl::EndQuery(gl::TIME_ELAPSED);
+        }
+    }
+
+    // Results usually take a frame or two to come back, checking doesn't stall
+    pub fn is_available(&self) -> bool {
+        let mut available: GLint = 0;
+        unsafe {
+            gl::GetQueryObjectiv(self.id, gl::QUERY_RESULT_AVAILABLE, &mut available);
+        }
+        available != 0
+    }
+
+    // Blocks until the result is available
+    pub fn elapsed(&self) -> Duration {
+        let mut elapsed = 0u64;
+        unsafe {
+            gl::GetQueryObjectui64v(self.id, gl::QUERY_RESULT, &mut elapsed);
+        }
+        Duration::from_nanos(elapsed)
+    }
+}
+
+fn has_extension(major: GLint, name: &str) -> bool {
+    unsafe {
+        if major >= 3 {
+            let mut count: GLint = 0;
+            gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+            (0..count.max(0) as GLuint).any(|i| {
+                let ext = gl::GetStringi(gl::EXTENSIONS, i);
+                !ext.is_null() && CStr::from_ptr(ext.cast()).to_bytes() == name.as_bytes()
+            })
+        } else {
+            // Pre 3.0 contexts list them all in one space separated string
+            let exts = gl::GetString(gl::EXTENSIONS);
+            !exts.is_null()
+                && CStr::from_ptr(exts.cast())
+                    .to_string_lossy()
+                    .split(' ')
+                    .any(|ext| ext == name)
+        }
+    }
+}
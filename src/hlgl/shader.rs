@@ -1,13 +1,15 @@
+use crate::{Error, Result};
 use crate::{
     gl,
     gl::types::{GLint, GLsizei, GLuint},
 };
 use pastey::paste;
+use regex::Regex;
 use std::collections::HashMap;
-use std::error::Error;
 use std::ffi::CString;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
+use std::sync::LazyLock;
 
 const INFO_LOG_MAX_LEN: usize = 1024;
 
@@ -263,6 +265,7 @@ uniform_mat_impl!(4x3fv, glm::TMat4x3<f32>);
 #[derive(Default)]
 pub struct ShaderBuilder {
     shader_ids: Vec<GLuint>,
+    compile_errors: Vec<Error>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -280,16 +283,116 @@ impl Display for ShaderKind {
     }
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Display for DiagnosticSeverity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticSeverity::Error => write!(f, "error"),
+            DiagnosticSeverity::Warning => write!(f, "warning"),
+            DiagnosticSeverity::Info => write!(f, "info"),
+        }
+    }
+}
+
+// A single message out of a shader compile or program link info log
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShaderDiagnostic {
+    pub line: Option<u32>,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+impl Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}: {}", line, self.severity, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+impl ShaderDiagnostic {
+    // Info log formats differ between vendors, these cover Mesa, NVIDIA and AMD/Intel/Apple:
+    //   0:12(5): error: message
+    //   0(12) : error C0000: message
+    //   ERROR: 0:12: message
+    // Anything else is kept as-is without a line number.
+    pub fn parse_info_log(info_log: &str) -> Vec<Self> {
+        static MESA_RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"^\d+:(\d+)\(\d+\):\s*(error|warning|info)\s*:\s*(.*)$").unwrap()
+        });
+        static NVIDIA_RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"^\d+\((\d+)\)\s*:\s*(error|warning|info)\s*\w*\s*:\s*(.*)$").unwrap()
+        });
+        static AMD_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^(ERROR|WARNING|INFO):\s*\d+:(\d+):\s*(.*)$").unwrap());
+
+        let mut diagnostics = vec![];
+        for line in info_log.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let diagnostic =
+                if let Some(caps) = MESA_RE.captures(line).or_else(|| NVIDIA_RE.captures(line)) {
+                    Self {
+                        line: caps[1].parse().ok(),
+                        severity: DiagnosticSeverity::from_log_str(&caps[2]),
+                        message: caps[3].to_owned(),
+                    }
+                } else if let Some(caps) = AMD_RE.captures(line) {
+                    Self {
+                        line: caps[2].parse().ok(),
+                        severity: DiagnosticSeverity::from_log_str(&caps[1]),
+                        message: caps[3].to_owned(),
+                    }
+                } else {
+                    Self {
+                        line: None,
+                        severity: if line.to_lowercase().starts_with("warning") {
+                            DiagnosticSeverity::Warning
+                        } else {
+                            DiagnosticSeverity::Error
+                        },
+                        message: line.to_owned(),
+                    }
+                };
+            diagnostics.push(diagnostic);
+        }
+
+        diagnostics
+    }
+}
+
+impl DiagnosticSeverity {
+    fn from_log_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "warning" => DiagnosticSeverity::Warning,
+            "info" => DiagnosticSeverity::Info,
+            _ => DiagnosticSeverity::Error,
+        }
+    }
+}
+
 impl ShaderBuilder {
     pub fn with_src_file(
         self,
         kind: ShaderKind,
         path: impl AsRef<std::path::Path>,
     ) -> std::io::Result<Self> {
-        std::fs::read_to_string(path).map(|src| self.with_src(kind, &src))
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .map(|src| self.with_named_src(kind, &path.display().to_string(), &src))
     }
 
-    pub fn with_src(mut self, kind: ShaderKind, src: &str) -> Self {
+    pub fn with_src(self, kind: ShaderKind, src: &str) -> Self {
+        self.with_named_src(kind, "<inline>", src)
+    }
+
+    // The name only shows up in errors, usually the file the source came from
+    pub fn with_named_src(mut self, kind: ShaderKind, name: &str, src: &str) -> Self {
         let id: u32 = unsafe {
             let gl_kind = match kind {
                 ShaderKind::Vertex => gl::VERTEX_SHADER,
@@ -306,18 +409,36 @@ impl ShaderBuilder {
             );
             gl::CompileShader(id);
 
-            if Self::check_compile(id, kind) { id } else { 0 }
+            id
         };
 
-        if id != 0 {
-            self.shader_ids.push(id);
+        match Self::check_compile(id, kind, name) {
+            Ok(()) => self.shader_ids.push(id),
+            Err(e) => {
+                unsafe {
+                    log::trace!("Deleting shader with id: {}", id);
+                    gl::DeleteShader(id);
+                }
+                self.compile_errors.push(e);
+            }
         }
 
         self
     }
 
-    pub fn try_link(self) -> Result<Shader, Box<dyn Error>> {
-        let id = unsafe {
+    // Fails with the first compile error if any stage failed to compile
+    pub fn try_link(mut self) -> Result<Shader> {
+        if !self.compile_errors.is_empty() {
+            unsafe {
+                for shader_id in self.shader_ids {
+                    log::trace!("Deleting shader with id: {}", shader_id);
+                    gl::DeleteShader(shader_id);
+                }
+            }
+            return Err(self.compile_errors.swap_remove(0));
+        }
+
+        let result = unsafe {
             let id = gl::CreateProgram();
             log::trace!("Created shader program with id: {}", id);
 
@@ -326,7 +447,7 @@ impl ShaderBuilder {
             }
 
             gl::LinkProgram(id);
-            if Self::check_link(id) { id } else { 0 }
+            Self::check_link(id).map(|_| id)
         };
 
         unsafe {
@@ -336,18 +457,14 @@ impl ShaderBuilder {
             }
         }
 
-        if id != 0 {
-            Ok(Shader {
-                id,
-                uniform_locs: Default::default(),
-                attrib_locs: Default::default(),
-            })
-        } else {
-            Err(Box::from("Failed to link shader module"))
-        }
+        result.map(|id| Shader {
+            id,
+            uniform_locs: Default::default(),
+            attrib_locs: Default::default(),
+        })
     }
 
-    fn check_compile(id: GLuint, kind: ShaderKind) -> bool {
+    fn check_compile(id: GLuint, kind: ShaderKind, name: &str) -> Result<()> {
         unsafe {
             let mut success = 0;
             gl::GetShaderiv(id, gl::COMPILE_STATUS, &mut success);
@@ -362,19 +479,20 @@ impl ShaderBuilder {
                 );
                 info_log.set_len(log_len as usize);
 
-                log::error!(
-                    "Error(s) while compiling {} shader (id: {}):\n{}",
-                    kind,
-                    id,
-                    String::from_utf8_lossy(&info_log).trim_end()
-                );
+                return Err(Error::ShaderCompile {
+                    stage: kind,
+                    source_name: name.to_owned(),
+                    diagnostics: ShaderDiagnostic::parse_info_log(&String::from_utf8_lossy(
+                        &info_log,
+                    )),
+                });
             }
-
-            success != 0
         }
+
+        Ok(())
     }
 
-    fn check_link(id: GLuint) -> bool {
+    fn check_link(id: GLuint) -> Result<()> {
         unsafe {
             let mut success: GLint = 0;
             gl::GetProgramiv(id, gl::LINK_STATUS, &mut success);
@@ -389,14 +507,69 @@ impl ShaderBuilder {
                 );
                 info_log.set_len(log_len as usize);
 
-                log::error!(
-                    "Error(s) while linking shader program (id: {}):\n{}",
-                    id,
-                    String::from_utf8_lossy(&info_log).trim_end()
-                );
-            }
+                log::trace!("Deleting shader program with id: {}", id);
+                gl::DeleteProgram(id);
 
-            success != 0
+                return Err(Error::ShaderLink {
+                    diagnostics: ShaderDiagnostic::parse_info_log(&String::from_utf8_lossy(
+                        &info_log,
+                    )),
+                });
+            }
         }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_mesa_info_log() {
+        let diagnostics = ShaderDiagnostic::parse_info_log(
+            "0:12(5): error: `foo' undeclared\n0:3(1): warning: unused variable\n",
+        );
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                ShaderDiagnostic {
+                    line: Some(12),
+                    severity: DiagnosticSeverity::Error,
+                    message: "`foo' undeclared".to_owned(),
+                },
+                ShaderDiagnostic {
+                    line: Some(3),
+                    severity: DiagnosticSeverity::Warning,
+                    message: "unused variable".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_nvidia_info_log() {
+        let diagnostics =
+            ShaderDiagnostic::parse_info_log("0(7) : error C0000: syntax error, unexpected '}'");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(7));
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+        assert_eq!(diagnostics[0].message, "syntax error, unexpected '}'");
+    }
+
+    #[test]
+    fn parse_amd_info_log() {
+        let diagnostics = ShaderDiagnostic::parse_info_log(
+            "ERROR: 0:21: 'bar' : undeclared identifier\nERROR: 1 compilation errors.",
+        );
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].line, Some(21));
+        assert_eq!(diagnostics[0].message, "'bar' : undeclared identifier");
+        assert_eq!(diagnostics[1].line, None);
+        assert_eq!(diagnostics[1].severity, DiagnosticSeverity::Error);
     }
 }
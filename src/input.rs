@@ -1,11 +1,22 @@
+mod action;
+mod cursor;
+mod gamepad;
+mod state;
+
+pub use action::*;
+pub use cursor::*;
+pub use gamepad::*;
 pub use sdl3::keyboard::{Keycode, Mod, Scancode};
+pub use state::*;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum MouseButton {
     Unknown,
     Left,
     Middle,
     Right,
+    X1,
+    X2,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -27,6 +38,8 @@ impl From<sdl3::mouse::MouseButton> for MouseButton {
             sdl3::mouse::MouseButton::Left => Self::Left,
             sdl3::mouse::MouseButton::Middle => Self::Middle,
             sdl3::mouse::MouseButton::Right => Self::Right,
+            sdl3::mouse::MouseButton::X1 => Self::X1,
+            sdl3::mouse::MouseButton::X2 => Self::X2,
             _ => Self::Unknown,
         }
     }
@@ -0,0 +1,623 @@
+use crate::input::{GamepadAxis, GamepadButton, InputState, Mod, MouseButton, Scancode};
+use crate::{Error, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+// Modifiers a binding requires, either side of the keyboard counts
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Chord {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub gui: bool,
+}
+
+impl Chord {
+    pub fn from_mods(mods: Mod) -> Self {
+        Self {
+            ctrl: mods.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+            shift: mods.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+            alt: mods.intersects(Mod::LALTMOD | Mod::RALTMOD),
+            gui: mods.intersects(Mod::LGUIMOD | Mod::RGUIMOD),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // A binding without modifiers ignores them (so Shift+W still walks), otherwise the held
+    // modifiers have to match exactly (so Ctrl+S doesn't also trigger Ctrl+Shift+S)
+    fn matches(&self, mods: Mod) -> bool {
+        self.is_empty() || *self == Self::from_mods(mods)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum BindingInput {
+    Key(Scancode),
+    Mouse(MouseButton),
+    // Matches the button on any connected gamepad
+    Gamepad(GamepadButton),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Binding {
+    pub input: BindingInput,
+    pub chord: Chord,
+}
+
+impl Binding {
+    pub fn key(scancode: Scancode) -> Self {
+        Self {
+            input: BindingInput::Key(scancode),
+            chord: Chord::default(),
+        }
+    }
+
+    pub fn mouse(button: MouseButton) -> Self {
+        Self {
+            input: BindingInput::Mouse(button),
+            chord: Chord::default(),
+        }
+    }
+
+    pub fn gamepad(button: GamepadButton) -> Self {
+        Self {
+            input: BindingInput::Gamepad(button),
+            chord: Chord::default(),
+        }
+    }
+
+    pub fn with_chord(mut self, chord: Chord) -> Self {
+        self.chord = chord;
+        self
+    }
+
+    fn is_down(&self, input: &InputState) -> bool {
+        self.chord.matches(input.mods())
+            && match self.input {
+                BindingInput::Key(scancode) => input.is_key_down(scancode),
+                BindingInput::Mouse(button) => input.is_mouse_down(button),
+                BindingInput::Gamepad(button) => {
+                    input.gamepads().any(|(_, pad)| pad.is_button_down(button))
+                }
+            }
+    }
+
+    fn was_pressed(&self, input: &InputState) -> bool {
+        self.chord.matches(input.mods())
+            && match self.input {
+                BindingInput::Key(scancode) => input.was_pressed_this_frame(scancode),
+                BindingInput::Mouse(button) => input.was_mouse_pressed_this_frame(button),
+                BindingInput::Gamepad(button) => input
+                    .gamepads()
+                    .any(|(_, pad)| pad.was_pressed_this_frame(button)),
+            }
+    }
+
+    // Modifiers are ignored here, they're often let go of before the key itself
+    fn was_released(&self, input: &InputState) -> bool {
+        match self.input {
+            BindingInput::Key(scancode) => input.was_released_this_frame(scancode),
+            BindingInput::Mouse(button) => input.was_mouse_released_this_frame(button),
+            BindingInput::Gamepad(button) => input
+                .gamepads()
+                .any(|(_, pad)| pad.was_released_this_frame(button)),
+        }
+    }
+}
+
+const MOUSE_BUTTON_NAMES: [(MouseButton, &str); 5] = [
+    (MouseButton::Left, "MouseLeft"),
+    (MouseButton::Middle, "MouseMiddle"),
+    (MouseButton::Right, "MouseRight"),
+    (MouseButton::X1, "MouseX1"),
+    (MouseButton::X2, "MouseX2"),
+];
+
+const GAMEPAD_BUTTON_NAMES: [(GamepadButton, &str); 17] = [
+    (GamepadButton::South, "PadSouth"),
+    (GamepadButton::East, "PadEast"),
+    (GamepadButton::West, "PadWest"),
+    (GamepadButton::North, "PadNorth"),
+    (GamepadButton::Back, "PadBack"),
+    (GamepadButton::Guide, "PadGuide"),
+    (GamepadButton::Start, "PadStart"),
+    (GamepadButton::LeftStick, "PadLeftStick"),
+    (GamepadButton::RightStick, "PadRightStick"),
+    (GamepadButton::LeftShoulder, "PadLeftShoulder"),
+    (GamepadButton::RightShoulder, "PadRightShoulder"),
+    (GamepadButton::DPadUp, "PadUp"),
+    (GamepadButton::DPadDown, "PadDown"),
+    (GamepadButton::DPadLeft, "PadLeft"),
+    (GamepadButton::DPadRight, "PadRight"),
+    (GamepadButton::Misc1, "PadMisc1"),
+    (GamepadButton::Touchpad, "PadTouchpad"),
+];
+
+const GAMEPAD_AXIS_NAMES: [(GamepadAxis, &str); 6] = [
+    (GamepadAxis::LeftX, "PadLeftX"),
+    (GamepadAxis::LeftY, "PadLeftY"),
+    (GamepadAxis::RightX, "PadRightX"),
+    (GamepadAxis::RightY, "PadRightY"),
+    (GamepadAxis::TriggerLeft, "PadTriggerLeft"),
+    (GamepadAxis::TriggerRight, "PadTriggerRight"),
+];
+
+fn find_by_name<T: Copy>(names: &[(T, &str)], name: &str) -> Option<T> {
+    names
+        .iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|(v, _)| *v)
+}
+
+fn find_name<T: PartialEq>(names: &[(T, &'static str)], value: T) -> Option<&'static str> {
+    names.iter().find(|(v, _)| *v == value).map(|(_, n)| *n)
+}
+
+// Parses things like "Space", "Ctrl+Shift+S" or "Alt+MouseLeft". Key names are SDL's scancode
+// names, so they can contain '+' themselves ("Keypad +"), which is why modifiers are stripped
+// off the front instead of splitting. Names can also be quoted ("\"Keypad |\""), which is how
+// the ones containing the ActionMap separator are written.
+impl FromStr for Binding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut chord = Chord::default();
+        let mut rest = s.trim();
+
+        while let Some((modifier, tail)) = rest.split_once('+') {
+            let flag = match modifier.trim().to_lowercase().as_str() {
+                "ctrl" | "control" => &mut chord.ctrl,
+                "shift" => &mut chord.shift,
+                "alt" | "option" => &mut chord.alt,
+                "gui" | "cmd" | "super" | "meta" | "win" => &mut chord.gui,
+                _ => break,
+            };
+            *flag = true;
+            rest = tail.trim_start();
+        }
+
+        if let Some(quoted) = rest.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+            rest = quoted;
+        }
+        if rest.is_empty() {
+            return Err(Error::BindingParse(format!("missing key in binding '{s}'")));
+        }
+
+        let input = if let Some(button) = find_by_name(&MOUSE_BUTTON_NAMES, rest) {
+            BindingInput::Mouse(button)
+        } else if let Some(button) = find_by_name(&GAMEPAD_BUTTON_NAMES, rest) {
+            BindingInput::Gamepad(button)
+        } else if let Some(scancode) = Scancode::from_name(rest) {
+            BindingInput::Key(scancode)
+        } else {
+            return Err(Error::BindingParse(format!(
+                "unknown key '{rest}' in '{s}'"
+            )));
+        };
+
+        Ok(Self { input, chord })
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (held, name) in [
+            (self.chord.ctrl, "Ctrl"),
+            (self.chord.shift, "Shift"),
+            (self.chord.alt, "Alt"),
+            (self.chord.gui, "Gui"),
+        ] {
+            if held {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        match self.input {
+            BindingInput::Key(scancode) => {
+                let name = scancode.name();
+                if name.contains('|') {
+                    write!(f, "\"{name}\"")
+                } else {
+                    write!(f, "{name}")
+                }
+            }
+            BindingInput::Mouse(button) => write!(
+                f,
+                "{}",
+                find_name(&MOUSE_BUTTON_NAMES, button).unwrap_or("MouseUnknown")
+            ),
+            BindingInput::Gamepad(button) => write!(
+                f,
+                "{}",
+                find_name(&GAMEPAD_BUTTON_NAMES, button).unwrap_or("PadUnknown")
+            ),
+        }
+    }
+}
+
+// One direction of an axis, written as "-A" or "+D" in config files, or an analog gamepad axis
+// written without a sign ("PadLeftX")
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AxisBinding {
+    Negative(Binding),
+    Positive(Binding),
+    Gamepad(GamepadAxis),
+}
+
+impl FromStr for AxisBinding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some(binding) = s.strip_prefix('-') {
+            Ok(AxisBinding::Negative(binding.parse()?))
+        } else if let Some(binding) = s.strip_prefix('+') {
+            Ok(AxisBinding::Positive(binding.parse()?))
+        } else if let Some(axis) = find_by_name(&GAMEPAD_AXIS_NAMES, s) {
+            Ok(AxisBinding::Gamepad(axis))
+        } else {
+            Err(Error::BindingParse(format!(
+                "axis binding '{s}' needs a direction, e.g. -{s} or +{s}"
+            )))
+        }
+    }
+}
+
+impl Display for AxisBinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AxisBinding::Negative(binding) => write!(f, "-{binding}"),
+            AxisBinding::Positive(binding) => write!(f, "+{binding}"),
+            AxisBinding::Gamepad(axis) => write!(
+                f,
+                "{}",
+                find_name(&GAMEPAD_AXIS_NAMES, *axis).unwrap_or("PadUnknown")
+            ),
+        }
+    }
+}
+
+// Named actions and axes, each bound to any number of inputs. The text format is one binding
+// list per line, alternatives separated by '|' (quote key names containing one):
+//
+//   # comment
+//   action jump = Space | MouseLeft
+//   action save = Ctrl+S
+//   axis move_x = -A | +D | -Left | +Right
+//   action or = "Keypad |"
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ActionMap {
+    actions: HashMap<String, Vec<Binding>>,
+    axes: HashMap<String, Vec<AxisBinding>>,
+}
+
+impl ActionMap {
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        let bindings = self.actions.entry(action.to_owned()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn bind_str(&mut self, action: &str, binding: &str) -> Result<()> {
+        self.bind(action, binding.parse()?);
+        Ok(())
+    }
+
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        let bindings = self.axes.entry(axis.to_owned()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn bind_axis_str(&mut self, axis: &str, binding: &str) -> Result<()> {
+        self.bind_axis(axis, binding.parse()?);
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, action: &str) {
+        self.actions.remove(action);
+    }
+
+    pub fn unbind_axis(&mut self, axis: &str) {
+        self.axes.remove(axis);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn axis_bindings(&self, axis: &str) -> &[AxisBinding] {
+        self.axes.get(axis).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+impl FromStr for ActionMap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut map = Self::default();
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let with_line = |e: Error| match e {
+                Error::BindingParse(msg) => Error::BindingParse(format!("line {}: {msg}", i + 1)),
+                e => e,
+            };
+
+            let Some((decl, bindings)) = line.split_once('=') else {
+                return Err(with_line(Error::BindingParse(format!(
+                    "expected '<action|axis> <name> = <bindings>', got '{line}'"
+                ))));
+            };
+
+            let (kind, name) = decl
+                .trim()
+                .split_once(char::is_whitespace)
+                .unwrap_or((decl, ""));
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(with_line(Error::BindingParse("missing name".to_owned())));
+            }
+            let is_axis = match kind {
+                "action" => false,
+                "axis" => true,
+                _ => {
+                    return Err(with_line(Error::BindingParse(format!(
+                        "unknown declaration '{kind}', expected 'action' or 'axis'"
+                    ))));
+                }
+            };
+
+            for binding in split_bindings(bindings).filter(|b| !b.trim().is_empty()) {
+                if is_axis {
+                    map.bind_axis_str(name, binding).map_err(with_line)?;
+                } else {
+                    map.bind_str(name, binding).map_err(with_line)?;
+                }
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+// Splits a binding list on the '|'s that aren't inside quotes
+fn split_bindings(s: &str) -> impl Iterator<Item = &str> {
+    let mut quoted = false;
+    s.split(move |c| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        c == '|' && !quoted
+    })
+}
+
+impl Display for ActionMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn join<T: Display>(bindings: &[T]) -> String {
+            bindings
+                .iter()
+                .map(T::to_string)
+                .collect::<Vec<_>>()
+                .join(" | ")
+        }
+
+        // Sorted so saved files diff nicely
+        for (name, bindings) in self.actions.iter().collect::<BTreeMap<_, _>>() {
+            writeln!(f, "action {} = {}", name, join(bindings.as_slice()))?;
+        }
+        for (name, bindings) in self.axes.iter().collect::<BTreeMap<_, _>>() {
+            writeln!(f, "axis {} = {}", name, join(bindings.as_slice()))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Queries an ActionMap against the current input, see Engine::actions
+pub struct Actions<'a> {
+    map: &'a ActionMap,
+    input: &'a InputState,
+}
+
+impl<'a> Actions<'a> {
+    pub fn new(map: &'a ActionMap, input: &'a InputState) -> Self {
+        Self { map, input }
+    }
+
+    pub fn down(&self, action: &str) -> bool {
+        self.map
+            .bindings(action)
+            .iter()
+            .any(|b| b.is_down(self.input))
+    }
+
+    pub fn pressed(&self, action: &str) -> bool {
+        self.map
+            .bindings(action)
+            .iter()
+            .any(|b| b.was_pressed(self.input))
+    }
+
+    pub fn released(&self, action: &str) -> bool {
+        self.map
+            .bindings(action)
+            .iter()
+            .any(|b| b.was_released(self.input))
+    }
+
+    // Sum of all bound directions, clamped to [-1, 1]. Gamepad axes use whichever connected pad is
+    // tilted the furthest.
+    pub fn axis(&self, axis: &str) -> f32 {
+        let value: f32 = self
+            .map
+            .axis_bindings(axis)
+            .iter()
+            .map(|b| match b {
+                AxisBinding::Negative(b) if b.is_down(self.input) => -1.0,
+                AxisBinding::Positive(b) if b.is_down(self.input) => 1.0,
+                AxisBinding::Gamepad(axis) => self
+                    .input
+                    .gamepads()
+                    .map(|(_, pad)| pad.axis(*axis))
+                    .fold(0.0, |a: f32, b: f32| if b.abs() > a.abs() { b } else { a }),
+                _ => 0.0,
+            })
+            .sum();
+
+        value.clamp(-1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_binding() {
+        let binding: Binding = "Ctrl+Shift+S".parse().unwrap();
+        assert_eq!(binding.input, BindingInput::Key(Scancode::S));
+        assert!(binding.chord.ctrl && binding.chord.shift);
+        assert!(!binding.chord.alt && !binding.chord.gui);
+
+        let binding: Binding = "alt + mouseleft".parse().unwrap();
+        assert_eq!(binding.input, BindingInput::Mouse(MouseButton::Left));
+        assert!(binding.chord.alt);
+
+        let binding: Binding = "Ctrl+Keypad +".parse().unwrap();
+        assert_eq!(binding.input, BindingInput::Key(Scancode::KpPlus));
+
+        assert!("Ctrl+".parse::<Binding>().is_err());
+        assert!("Ctrl+NotAKey".parse::<Binding>().is_err());
+    }
+
+    #[test]
+    fn binding_round_trip() {
+        for s in [
+            "Space",
+            "Ctrl+Shift+S",
+            "Gui+MouseX2",
+            "Keypad +",
+            "\"Keypad |\"",
+            "Shift+\"Keypad ||\"",
+            "PadSouth",
+        ] {
+            let binding: Binding = s.parse().unwrap();
+            assert_eq!(binding.to_string(), s);
+        }
+    }
+
+    #[test]
+    fn action_map_round_trip() {
+        let text = "\
+# movement
+action jump = Space | MouseLeft
+action save = Ctrl+S
+axis move_x = -A | +D | PadLeftX
+";
+        let map: ActionMap = text.parse().unwrap();
+        assert_eq!(map.bindings("jump").len(), 2);
+        assert_eq!(map.axis_bindings("move_x").len(), 3);
+
+        let reparsed: ActionMap = map.to_string().parse().unwrap();
+        assert_eq!(reparsed, map);
+
+        // Keys named after the separator are quoted
+        let mut map = ActionMap::default();
+        map.bind("or", Binding::key(Scancode::KpVerticalBar));
+        map.bind("or", Binding::key(Scancode::KpDblVerticalBar));
+        map.bind_axis(
+            "zoom",
+            AxisBinding::Positive(Binding::key(Scancode::KpVerticalBar)),
+        );
+        let reparsed: ActionMap = map.to_string().parse().unwrap();
+        assert_eq!(reparsed, map);
+    }
+
+    #[test]
+    fn action_map_errors_have_line_numbers() {
+        let err = "action jump = Space\naxis move_x = A\n"
+            .parse::<ActionMap>()
+            .unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+
+        // The declaration is checked even without bindings
+        let err = "action jump = Space\nfoo jump =\n"
+            .parse::<ActionMap>()
+            .unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+        assert!("axis move_x =\n".parse::<ActionMap>().is_ok());
+    }
+
+    #[test]
+    fn query_actions() {
+        let mut map = ActionMap::default();
+        map.bind_str("save", "Ctrl+S").unwrap();
+        map.bind_str("walk", "W").unwrap();
+        map.bind_axis_str("move_x", "-A").unwrap();
+        map.bind_axis_str("move_x", "+D").unwrap();
+
+        let mut input = InputState::default();
+        input.begin_frame();
+        input.key_down(Scancode::LCtrl, Mod::LCTRLMOD);
+        input.key_down(Scancode::S, Mod::LCTRLMOD);
+        input.key_down(Scancode::W, Mod::LCTRLMOD);
+        input.key_down(Scancode::D, Mod::LCTRLMOD);
+
+        let actions = Actions::new(&map, &input);
+        assert!(actions.pressed("save"));
+        assert!(actions.down("walk"));
+        assert_eq!(actions.axis("move_x"), 1.0);
+        assert!(!actions.pressed("unbound"));
+
+        input.begin_frame();
+        input.key_down(Scancode::LShift, Mod::LCTRLMOD | Mod::LSHIFTMOD);
+        let actions = Actions::new(&map, &input);
+        assert!(!actions.down("save"));
+        assert!(actions.down("walk"));
+    }
+
+    #[test]
+    fn query_gamepad_actions() {
+        let mut map = ActionMap::default();
+        map.bind_str("jump", "PadSouth").unwrap();
+        map.bind_axis_str("move_x", "PadLeftX").unwrap();
+        map.bind_axis_str("move_x", "+D").unwrap();
+
+        let mut input = InputState::default();
+        input.gamepad_added(1);
+        input.gamepad_added(2);
+        input.begin_frame();
+        input.gamepad_button_down(2, GamepadButton::South);
+        input.gamepad_axis(1, GamepadAxis::LeftX, -i16::MAX);
+        input.gamepad_axis(2, GamepadAxis::LeftX, i16::MAX / 2);
+
+        let actions = Actions::new(&map, &input);
+        assert!(actions.pressed("jump"));
+        assert_eq!(actions.axis("move_x"), -1.0);
+
+        input.key_down(Scancode::D, Mod::NOMOD);
+        let actions = Actions::new(&map, &input);
+        assert_eq!(actions.axis("move_x"), 0.0);
+    }
+}
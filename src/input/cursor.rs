@@ -0,0 +1,90 @@
+use crate::{Error, Result};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SystemCursor {
+    Arrow,
+    IBeam,
+    Wait,
+    WaitArrow,
+    Crosshair,
+    Hand,
+    NotAllowed,
+    ResizeAll,
+    ResizeHorizontal,
+    ResizeVertical,
+    // Diagonals, named after the corners they point between
+    ResizeNwse,
+    ResizeNesw,
+}
+
+impl From<SystemCursor> for sdl3::mouse::SystemCursor {
+    fn from(value: SystemCursor) -> Self {
+        use sdl3::mouse::SystemCursor as Sdl;
+
+        match value {
+            SystemCursor::Arrow => Sdl::Arrow,
+            SystemCursor::IBeam => Sdl::IBeam,
+            SystemCursor::Wait => Sdl::Wait,
+            SystemCursor::WaitArrow => Sdl::WaitArrow,
+            SystemCursor::Crosshair => Sdl::Crosshair,
+            SystemCursor::Hand => Sdl::Hand,
+            SystemCursor::NotAllowed => Sdl::No,
+            SystemCursor::ResizeAll => Sdl::SizeAll,
+            SystemCursor::ResizeHorizontal => Sdl::SizeWE,
+            SystemCursor::ResizeVertical => Sdl::SizeNS,
+            SystemCursor::ResizeNwse => Sdl::SizeNWSE,
+            SystemCursor::ResizeNesw => Sdl::SizeNESW,
+        }
+    }
+}
+
+// A cursor image, hand it to Engine::set_cursor to use it
+pub struct Cursor {
+    raw: sdl3::mouse::Cursor,
+}
+
+impl Cursor {
+    pub fn system(kind: SystemCursor) -> Result<Self> {
+        sdl3::mouse::Cursor::from_system(kind.into())
+            .map(|raw| Self { raw })
+            .map_err(|e| Error::Cursor(e.to_string()))
+    }
+
+    // pixels are 8-bit RGBA, rows top to bottom, (hot_x, hot_y) is the pixel that clicks
+    pub fn from_rgba(
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        hot_x: i32,
+        hot_y: i32,
+    ) -> Result<Self> {
+        if pixels.len() != width as usize * height as usize * 4 {
+            return Err(Error::Cursor(format!(
+                "expected {} bytes of RGBA data for a {}x{} cursor, got {}",
+                width as usize * height as usize * 4,
+                width,
+                height,
+                pixels.len()
+            )));
+        }
+
+        // SDL copies the pixels when creating the cursor, the surface only has to borrow them
+        let mut pixels = pixels.to_vec();
+        let surface = sdl3::surface::Surface::from_data(
+            &mut pixels,
+            width,
+            height,
+            width * 4,
+            sdl3::pixels::PixelFormatEnum::RGBA32.into(),
+        )
+        .map_err(|e| Error::Cursor(e.to_string()))?;
+
+        sdl3::mouse::Cursor::from_surface(surface, hot_x, hot_y)
+            .map(|raw| Self { raw })
+            .map_err(|e| Error::Cursor(e.to_string()))
+    }
+
+    pub(crate) fn set(&self) {
+        self.raw.set();
+    }
+}
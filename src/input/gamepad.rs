@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    Unknown,
+    // Face buttons by position, A/B/X/Y on an Xbox layout
+    South,
+    East,
+    West,
+    North,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Misc1,
+    Touchpad,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    TriggerLeft,
+    TriggerRight,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GamepadAction {
+    Press,
+    Release,
+}
+
+impl From<sdl3::gamepad::Button> for GamepadButton {
+    fn from(value: sdl3::gamepad::Button) -> Self {
+        use sdl3::gamepad::Button;
+
+        match value {
+            Button::South => Self::South,
+            Button::East => Self::East,
+            Button::West => Self::West,
+            Button::North => Self::North,
+            Button::Back => Self::Back,
+            Button::Guide => Self::Guide,
+            Button::Start => Self::Start,
+            Button::LeftStick => Self::LeftStick,
+            Button::RightStick => Self::RightStick,
+            Button::LeftShoulder => Self::LeftShoulder,
+            Button::RightShoulder => Self::RightShoulder,
+            Button::DPadUp => Self::DPadUp,
+            Button::DPadDown => Self::DPadDown,
+            Button::DPadLeft => Self::DPadLeft,
+            Button::DPadRight => Self::DPadRight,
+            Button::Misc1 => Self::Misc1,
+            Button::Touchpad => Self::Touchpad,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl From<sdl3::gamepad::Axis> for GamepadAxis {
+    fn from(value: sdl3::gamepad::Axis) -> Self {
+        use sdl3::gamepad::Axis;
+
+        match value {
+            Axis::LeftX => Self::LeftX,
+            Axis::LeftY => Self::LeftY,
+            Axis::RightX => Self::RightX,
+            Axis::RightY => Self::RightY,
+            Axis::TriggerLeft => Self::TriggerLeft,
+            Axis::TriggerRight => Self::TriggerRight,
+        }
+    }
+}
+
+impl GamepadAxis {
+    pub fn is_trigger(&self) -> bool {
+        matches!(self, GamepadAxis::TriggerLeft | GamepadAxis::TriggerRight)
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+// Anything inside the dead zone reads as 0, the rest is rescaled so values still start at 0 right
+// outside of it and reach 1 at full tilt
+pub fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let magnitude = value.abs();
+    if magnitude <= dead_zone {
+        0.0
+    } else {
+        value.signum() * ((magnitude - dead_zone) / (1.0 - dead_zone)).min(1.0)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GamepadState {
+    buttons_down: HashSet<GamepadButton>,
+    buttons_pressed: HashSet<GamepadButton>,
+    buttons_released: HashSet<GamepadButton>,
+    axes: [f32; 6],
+}
+
+impl GamepadState {
+    pub fn is_button_down(&self, button: GamepadButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    pub fn was_pressed_this_frame(&self, button: GamepadButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn was_released_this_frame(&self, button: GamepadButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    // Sticks are in [-1, 1] (down/right positive), triggers in [0, 1], dead zones already applied
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis.index()]
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+    }
+
+    pub(crate) fn button_down(&mut self, button: GamepadButton) {
+        if self.buttons_down.insert(button) {
+            self.buttons_pressed.insert(button);
+        }
+    }
+
+    pub(crate) fn button_up(&mut self, button: GamepadButton) {
+        if self.buttons_down.remove(&button) {
+            self.buttons_released.insert(button);
+        }
+    }
+
+    // Returns the new value if it changed after applying the dead zone
+    pub(crate) fn set_axis(&mut self, axis: GamepadAxis, raw: i16, dead_zone: f32) -> Option<f32> {
+        let value = apply_dead_zone((raw as f32 / i16::MAX as f32).clamp(-1.0, 1.0), dead_zone);
+        if self.axes[axis.index()] == value {
+            None
+        } else {
+            self.axes[axis.index()] = value;
+            Some(value)
+        }
+    }
+
+    pub(crate) fn release_all(&mut self) {
+        self.buttons_released.extend(self.buttons_down.drain());
+        self.axes = [0.0; 6];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    #[test]
+    fn dead_zone_rescales() {
+        assert_eq!(apply_dead_zone(0.1, 0.2), 0.0);
+        assert_eq!(apply_dead_zone(-0.2, 0.2), 0.0);
+        assert_relative_eq!(apply_dead_zone(0.6, 0.2), 0.5);
+        assert_relative_eq!(apply_dead_zone(-0.6, 0.2), -0.5);
+        assert_relative_eq!(apply_dead_zone(1.0, 0.2), 1.0);
+    }
+
+    #[test]
+    fn axis_only_reports_changes() {
+        let mut pad = GamepadState::default();
+
+        assert_eq!(pad.set_axis(GamepadAxis::LeftX, 1000, 0.15), None);
+        assert_eq!(pad.set_axis(GamepadAxis::LeftX, i16::MAX, 0.15), Some(1.0));
+        assert_eq!(pad.set_axis(GamepadAxis::LeftX, i16::MAX, 0.15), None);
+        assert_eq!(pad.set_axis(GamepadAxis::LeftX, i16::MIN, 0.15), Some(-1.0));
+        assert_eq!(pad.axis(GamepadAxis::LeftX), -1.0);
+    }
+
+    #[test]
+    fn button_edges() {
+        let mut pad = GamepadState::default();
+
+        pad.begin_frame();
+        pad.button_down(GamepadButton::South);
+        assert!(pad.was_pressed_this_frame(GamepadButton::South));
+
+        pad.begin_frame();
+        assert!(pad.is_button_down(GamepadButton::South));
+        assert!(!pad.was_pressed_this_frame(GamepadButton::South));
+
+        pad.release_all();
+        assert!(!pad.is_button_down(GamepadButton::South));
+        assert!(pad.was_released_this_frame(GamepadButton::South));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::input::{GamepadAxis, GamepadButton, GamepadState, Mod, MouseButton, Scancode};
+use std::collections::{HashMap, HashSet};
+
+// Polled view of the keyboard and mouse, updated by the engine while it polls events. The
+// pressed/released edges cover events from the latest poll, so they're visible for exactly one
+// update/draw.
+pub struct InputState {
+    keys_down: HashSet<Scancode>,
+    keys_pressed: HashSet<Scancode>,
+    keys_released: HashSet<Scancode>,
+
+    mouse_down: HashSet<MouseButton>,
+    mouse_pressed: HashSet<MouseButton>,
+    mouse_released: HashSet<MouseButton>,
+
+    mouse_pos: (f32, f32),
+    mouse_delta: (f32, f32),
+    wheel_delta: (f32, f32),
+
+    mods: Mod,
+
+    gamepads: HashMap<u32, GamepadState>,
+    stick_dead_zone: f32,
+    trigger_dead_zone: f32,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self {
+            keys_down: HashSet::default(),
+            keys_pressed: HashSet::default(),
+            keys_released: HashSet::default(),
+            mouse_down: HashSet::default(),
+            mouse_pressed: HashSet::default(),
+            mouse_released: HashSet::default(),
+            mouse_pos: (0.0, 0.0),
+            mouse_delta: (0.0, 0.0),
+            wheel_delta: (0.0, 0.0),
+            mods: Mod::NOMOD,
+            gamepads: HashMap::default(),
+            stick_dead_zone: 0.15,
+            trigger_dead_zone: 0.05,
+        }
+    }
+}
+
+impl InputState {
+    pub fn is_key_down(&self, scancode: Scancode) -> bool {
+        self.keys_down.contains(&scancode)
+    }
+
+    pub fn was_pressed_this_frame(&self, scancode: Scancode) -> bool {
+        self.keys_pressed.contains(&scancode)
+    }
+
+    pub fn was_released_this_frame(&self, scancode: Scancode) -> bool {
+        self.keys_released.contains(&scancode)
+    }
+
+    pub fn is_mouse_down(&self, button: MouseButton) -> bool {
+        self.mouse_down.contains(&button)
+    }
+
+    pub fn was_mouse_pressed_this_frame(&self, button: MouseButton) -> bool {
+        self.mouse_pressed.contains(&button)
+    }
+
+    pub fn was_mouse_released_this_frame(&self, button: MouseButton) -> bool {
+        self.mouse_released.contains(&button)
+    }
+
+    pub fn mouse_position(&self) -> (f32, f32) {
+        self.mouse_pos
+    }
+
+    // Summed over all motion events this frame
+    pub fn mouse_delta(&self) -> (f32, f32) {
+        self.mouse_delta
+    }
+
+    pub fn wheel_delta(&self) -> (f32, f32) {
+        self.wheel_delta
+    }
+
+    pub fn mods(&self) -> Mod {
+        self.mods
+    }
+
+    pub fn gamepad(&self, id: u32) -> Option<&GamepadState> {
+        self.gamepads.get(&id)
+    }
+
+    pub fn gamepads(&self) -> impl Iterator<Item = (u32, &GamepadState)> {
+        self.gamepads.iter().map(|(id, pad)| (*id, pad))
+    }
+
+    pub fn stick_dead_zone(&self) -> f32 {
+        self.stick_dead_zone
+    }
+
+    pub fn trigger_dead_zone(&self) -> f32 {
+        self.trigger_dead_zone
+    }
+
+    pub(crate) fn set_dead_zones(&mut self, stick: f32, trigger: f32) {
+        self.stick_dead_zone = stick.clamp(0.0, 0.99);
+        self.trigger_dead_zone = trigger.clamp(0.0, 0.99);
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.mouse_pressed.clear();
+        self.mouse_released.clear();
+        self.mouse_delta = (0.0, 0.0);
+        self.wheel_delta = (0.0, 0.0);
+        for pad in self.gamepads.values_mut() {
+            pad.begin_frame();
+        }
+    }
+
+    pub(crate) fn key_down(&mut self, scancode: Scancode, mods: Mod) {
+        self.mods = mods;
+        if self.keys_down.insert(scancode) {
+            self.keys_pressed.insert(scancode);
+        }
+    }
+
+    pub(crate) fn key_up(&mut self, scancode: Scancode, mods: Mod) {
+        self.mods = mods;
+        if self.keys_down.remove(&scancode) {
+            self.keys_released.insert(scancode);
+        }
+    }
+
+    pub(crate) fn mouse_button_down(&mut self, button: MouseButton, x: f32, y: f32) {
+        self.mouse_pos = (x, y);
+        if self.mouse_down.insert(button) {
+            self.mouse_pressed.insert(button);
+        }
+    }
+
+    pub(crate) fn mouse_button_up(&mut self, button: MouseButton, x: f32, y: f32) {
+        self.mouse_pos = (x, y);
+        if self.mouse_down.remove(&button) {
+            self.mouse_released.insert(button);
+        }
+    }
+
+    pub(crate) fn mouse_motion(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        self.mouse_pos = (x, y);
+        self.mouse_delta.0 += dx;
+        self.mouse_delta.1 += dy;
+    }
+
+    pub(crate) fn mouse_wheel(&mut self, scroll_x: f32, scroll_y: f32) {
+        self.wheel_delta.0 += scroll_x;
+        self.wheel_delta.1 += scroll_y;
+    }
+
+    pub(crate) fn gamepad_added(&mut self, id: u32) {
+        self.gamepads.entry(id).or_default();
+    }
+
+    pub(crate) fn gamepad_removed(&mut self, id: u32) {
+        self.gamepads.remove(&id);
+    }
+
+    // Events for pads that aren't connected (any more) are ignored, they can still trickle in
+    // after the removal
+    pub(crate) fn gamepad_button_down(&mut self, id: u32, button: GamepadButton) {
+        if let Some(pad) = self.gamepads.get_mut(&id) {
+            pad.button_down(button);
+        }
+    }
+
+    pub(crate) fn gamepad_button_up(&mut self, id: u32, button: GamepadButton) {
+        if let Some(pad) = self.gamepads.get_mut(&id) {
+            pad.button_up(button);
+        }
+    }
+
+    // Returns the dead zoned value if it changed
+    pub(crate) fn gamepad_axis(&mut self, id: u32, axis: GamepadAxis, raw: i16) -> Option<f32> {
+        let dead_zone = if axis.is_trigger() {
+            self.trigger_dead_zone
+        } else {
+            self.stick_dead_zone
+        };
+        self.gamepads.get_mut(&id)?.set_axis(axis, raw, dead_zone)
+    }
+
+    // We won't see the key/button up events for anything released while unfocused, so treat
+    // everything as released when focus goes away
+    pub(crate) fn release_all(&mut self) {
+        self.keys_released.extend(self.keys_down.drain());
+        self.mouse_released.extend(self.mouse_down.drain());
+        self.mods = Mod::NOMOD;
+        for pad in self.gamepads.values_mut() {
+            pad.release_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn key_edges_last_one_frame() {
+        let mut input = InputState::default();
+
+        input.begin_frame();
+        input.key_down(Scancode::W, Mod::NOMOD);
+        assert!(input.is_key_down(Scancode::W));
+        assert!(input.was_pressed_this_frame(Scancode::W));
+
+        // Key repeat shouldn't produce another press edge
+        input.begin_frame();
+        input.key_down(Scancode::W, Mod::NOMOD);
+        assert!(input.is_key_down(Scancode::W));
+        assert!(!input.was_pressed_this_frame(Scancode::W));
+
+        input.begin_frame();
+        input.key_up(Scancode::W, Mod::NOMOD);
+        assert!(!input.is_key_down(Scancode::W));
+        assert!(input.was_released_this_frame(Scancode::W));
+
+        input.begin_frame();
+        assert!(!input.was_released_this_frame(Scancode::W));
+    }
+
+    #[test]
+    fn press_and_release_in_one_frame() {
+        let mut input = InputState::default();
+
+        input.begin_frame();
+        input.mouse_button_down(MouseButton::Left, 1.0, 2.0);
+        input.mouse_button_up(MouseButton::Left, 3.0, 4.0);
+
+        assert!(!input.is_mouse_down(MouseButton::Left));
+        assert!(input.was_mouse_pressed_this_frame(MouseButton::Left));
+        assert!(input.was_mouse_released_this_frame(MouseButton::Left));
+        assert_eq!(input.mouse_position(), (3.0, 4.0));
+    }
+
+    #[test]
+    fn mouse_deltas_accumulate() {
+        let mut input = InputState::default();
+
+        input.begin_frame();
+        input.mouse_motion(10.0, 10.0, 2.0, -1.0);
+        input.mouse_motion(13.0, 8.0, 3.0, -2.0);
+        input.mouse_wheel(0.0, 1.5);
+        assert_eq!(input.mouse_delta(), (5.0, -3.0));
+        assert_eq!(input.wheel_delta(), (0.0, 1.5));
+
+        input.begin_frame();
+        assert_eq!(input.mouse_delta(), (0.0, 0.0));
+        assert_eq!(input.mouse_position(), (13.0, 8.0));
+    }
+
+    #[test]
+    fn focus_loss_releases_everything() {
+        let mut input = InputState::default();
+
+        input.begin_frame();
+        input.key_down(Scancode::LShift, Mod::LSHIFTMOD);
+        input.mouse_button_down(MouseButton::Right, 0.0, 0.0);
+
+        input.begin_frame();
+        input.release_all();
+        assert!(!input.is_key_down(Scancode::LShift));
+        assert!(input.was_released_this_frame(Scancode::LShift));
+        assert!(!input.is_mouse_down(MouseButton::Right));
+        assert!(input.was_mouse_released_this_frame(MouseButton::Right));
+        assert_eq!(input.mods(), Mod::NOMOD);
+    }
+
+    #[test]
+    fn removed_gamepads_stay_removed() {
+        let mut input = InputState::default();
+        input.gamepad_added(3);
+        input.gamepad_removed(3);
+
+        input.begin_frame();
+        input.gamepad_button_down(3, GamepadButton::South);
+        input.gamepad_button_up(3, GamepadButton::South);
+        assert_eq!(input.gamepad_axis(3, GamepadAxis::LeftX, i16::MAX), None);
+        assert!(input.gamepad(3).is_none());
+        assert_eq!(input.gamepads().count(), 0);
+    }
+}
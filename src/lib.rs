@@ -2,9 +2,16 @@ pub extern crate nalgebra_glm as glm;
 
 pub mod application;
 mod averagers;
+mod config;
+mod error;
+mod event;
+mod filters;
 pub mod gfx;
 pub mod hlgl;
 pub mod input;
+pub mod profiler;
+mod recording;
+mod scheduler;
 mod time;
 
 mod gl {
@@ -14,8 +21,17 @@ mod gl {
 
 pub use application::*;
 pub use averagers::*;
-pub use gfx::{Hsla, Hsva, Rgba};
+pub use config::*;
+pub use error::*;
+pub use event::*;
+pub use filters::*;
+pub use gfx::{Hsla, Hsva, Hwb, Lab, Lch, LinRgba, Oklab, Oklch, Rgba, Xyz};
+pub use recording::*;
+pub use scheduler::*;
 use sdl3::EventPump;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::time::Duration;
 pub use time::*;
 
 pub struct Engine {
@@ -23,7 +39,33 @@ pub struct Engine {
     pub frame_counter: FrameCounter,
 
     running: bool,
-
+    // Drives the fixed ticker (and through it update's dt and the scheduler), scaled by the time
+    // scale. The frame counter and limiter stay on real time.
+    game_clock: ScaledClock,
+    fixed_ticker: Ticker,
+    // Only present when running headless, everything is drawn into this instead of the window
+    offscreen_target: Option<hlgl::Framebuffer>,
+    clear_color: (f32, f32, f32, f32),
+    frame_limiter: FrameLimiter,
+    focused: bool,
+    minimized: bool,
+    display_scale: f32,
+    input: input::InputState,
+    action_map: input::ActionMap,
+    gamepads: HashMap<u32, sdl3::gamepad::Gamepad>,
+    gamepad_subsystem: sdl3::GamepadSubsystem,
+    // SDL frees the active cursor when dropped, so it has to live as long as it's in use
+    cursor: Option<input::Cursor>,
+    // Files dropped since the last DropBegin, handed over as a group on DropComplete
+    dropped_files: Vec<PathBuf>,
+    frame_index: u64,
+    recorder: Option<recording::Recorder>,
+    replayer: Option<recording::Replayer>,
+    // Delivered along with SDL's events on the next poll
+    injected: VecDeque<Event>,
+    scheduler: Scheduler<Engine>,
+
+    event_pump: EventPump,
     #[allow(dead_code)]
     ctx: sdl3::video::GLContext,
     pub window: sdl3::video::Window,
@@ -42,58 +84,455 @@ impl Engine {
         )
     }
 
+    pub fn fixed_step(&self) -> Duration {
+        self.fixed_ticker.interval()
+    }
+
+    // Panics unless the rate is positive and finite
+    pub fn set_fixed_step_rate(&mut self, steps_per_second: f64) {
+        self.fixed_ticker.set_rate(steps_per_second);
+    }
+
+    // Upper bound on fixed updates run in a single frame, extra accumulated time is dropped
+    pub fn set_max_fixed_steps(&mut self, max_steps: usize) {
+        self.fixed_ticker.set_max_ticks(Some(max_steps));
+    }
+
+    // Multiplies the dt passed to update, fixed update and the scheduler, 0.5 is half speed
+    pub fn set_time_scale(&mut self, scale: f64) {
+        self.game_clock.set_scale(scale);
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.game_clock.scale()
+    }
+
+    // Stops game time entirely, update keeps running with a dt of 0 and fixed updates stop
+    pub fn set_time_paused(&mut self, paused: bool) {
+        self.game_clock.set_paused(paused);
+    }
+
+    pub fn is_time_paused(&self) -> bool {
+        self.game_clock.is_paused()
+    }
+
+    // Clone it to build Tickers that follow the time scale
+    pub fn game_clock(&self) -> &ScaledClock {
+        &self.game_clock
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.offscreen_target.is_some()
+    }
+
+    // Flushes anything queued on g2d and reads back the current render target, so this is meant to
+    // be called at the end of Application::draw. The image is in pixels, which can be larger than
+    // window.size() on high pixel density displays.
+    pub fn screenshot(&mut self) -> gfx::Image {
+        self.g2d.draw(&self.window_ortho_projection());
+
+        match &self.offscreen_target {
+            Some(fb) => gfx::Image::from_framebuffer(fb),
+            None => {
+                let (width, height) = self.window.size_in_pixels();
+                gfx::Image::read_gl_pixels(0, 0, width, height)
+            }
+        }
+    }
+
     pub fn shutdown(&mut self) {
         self.running = false;
     }
 
-    pub fn vsync(&mut self) -> bool {
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    // Queues an event to be dispatched on the next poll as if it came from SDL, mostly for
+    // driving an Application from tests
+    pub fn inject(&mut self, event: Event) {
+        self.injected.push_back(event);
+    }
+
+    // Press and release in the same poll, so only the pressed/released edges are visible after it
+    pub fn inject_key_press(&mut self, scancode: input::Scancode) {
+        self.inject_key(scancode, input::KeyAction::Press);
+        self.inject_key(scancode, input::KeyAction::Release);
+    }
+
+    // Uses the keycode SDL names the same as the scancode, which is the US layout mapping
+    pub fn inject_key(&mut self, scancode: input::Scancode, action: input::KeyAction) {
+        self.inject(Event::Key {
+            keycode: input::Keycode::from_name(&scancode.name()),
+            scancode: Some(scancode),
+            keymod: self.input.mods(),
+            action,
+        });
+    }
+
+    pub fn inject_mouse_click(&mut self, x: f32, y: f32, button: input::MouseButton) {
+        self.inject_mouse_button(x, y, button, input::MouseAction::Press);
+        self.inject_mouse_button(x, y, button, input::MouseAction::Release);
+    }
+
+    pub fn inject_mouse_button(
+        &mut self,
+        x: f32,
+        y: f32,
+        button: input::MouseButton,
+        action: input::MouseAction,
+    ) {
+        self.inject(Event::MouseButton {
+            x,
+            y,
+            button,
+            clicks: 1,
+            action,
+        });
+    }
+
+    // The delta is relative to the last known mouse position, including injected events that
+    // haven't been delivered yet
+    pub fn inject_mouse_move(&mut self, x: f32, y: f32) {
+        let (last_x, last_y) = self
+            .injected
+            .iter()
+            .rev()
+            .find_map(|event| match event {
+                Event::MouseMotion { x, y, .. } | Event::MouseButton { x, y, .. } => Some((*x, *y)),
+                _ => None,
+            })
+            .unwrap_or_else(|| self.input.mouse_position());
+        self.inject(Event::MouseMotion {
+            x,
+            y,
+            dx: x - last_x,
+            dy: y - last_y,
+        });
+    }
+
+    pub fn inject_text(&mut self, text: &str) {
+        self.inject(Event::TextInput {
+            text: text.to_string(),
+        });
+    }
+
+    // Caps the frame rate by sleeping at the end of each frame, mostly useful with vsync off.
+    // Panics unless fps is positive and finite.
+    pub fn set_target_fps(&mut self, fps: Option<f64>) {
+        self.frame_limiter.set_target_fps(fps);
+    }
+
+    pub fn target_fps(&self) -> Option<f64> {
+        self.frame_limiter.target_fps()
+    }
+
+    // Frame rate cap used instead of the target fps while the window is unfocused
+    pub fn set_background_fps(&mut self, fps: Option<f64>) {
+        self.frame_limiter.set_background_fps(fps);
+    }
+
+    pub fn background_fps(&self) -> Option<f64> {
+        self.frame_limiter.background_fps()
+    }
+
+    pub fn input(&self) -> &input::InputState {
+        &self.input
+    }
+
+    pub fn actions(&self) -> input::Actions<'_> {
+        input::Actions::new(&self.action_map, &self.input)
+    }
+
+    pub fn action_map(&self) -> &input::ActionMap {
+        &self.action_map
+    }
+
+    pub fn action_map_mut(&mut self) -> &mut input::ActionMap {
+        &mut self.action_map
+    }
+
+    pub fn set_action_map(&mut self, action_map: input::ActionMap) {
+        self.action_map = action_map;
+    }
+
+    // Stick axes use the first value, triggers the second, both in [0, 1)
+    pub fn set_gamepad_dead_zones(&mut self, stick: f32, trigger: f32) {
+        self.input.set_dead_zones(stick, trigger);
+    }
+
+    pub fn gamepad_name(&self, id: u32) -> Option<String> {
+        self.gamepads.get(&id).and_then(|pad| pad.name())
+    }
+
+    // None if the clipboard is empty or doesn't hold text
+    pub fn clipboard_text(&self) -> Option<String> {
+        let clipboard = self.window.subsystem().clipboard();
+        if clipboard.has_clipboard_text() {
+            clipboard.clipboard_text().ok()
+        } else {
+            None
+        }
+    }
+
+    pub fn set_clipboard_text(&mut self, text: &str) -> Result<()> {
         self.window
             .subsystem()
-            .gl_get_swap_interval()
-            .map(|interval| interval == sdl3::video::SwapInterval::VSync)
-            .unwrap_or(false)
+            .clipboard()
+            .set_clipboard_text(text)
+            .map_err(|e| Error::Clipboard(e.to_string()))
+    }
+
+    pub fn show_cursor(&mut self, visible: bool) {
+        self.sdl.mouse().show_cursor(visible);
+    }
+
+    pub fn is_cursor_visible(&self) -> bool {
+        self.sdl.mouse().is_cursor_showing()
+    }
+
+    // Hides the cursor and keeps reporting motion deltas even when it would hit the screen edge
+    pub fn set_relative_mouse_mode(&mut self, enabled: bool) {
+        self.sdl
+            .mouse()
+            .set_relative_mouse_mode(&self.window, enabled);
+    }
+
+    pub fn relative_mouse_mode(&self) -> bool {
+        self.sdl.mouse().relative_mouse_mode(&self.window)
+    }
+
+    // Keeps the cursor from leaving the window while it has focus
+    pub fn set_cursor_confined(&mut self, confined: bool) {
+        self.window.set_mouse_grab(confined);
+    }
+
+    pub fn is_cursor_confined(&self) -> bool {
+        self.window.mouse_grab()
+    }
+
+    pub fn warp_mouse(&mut self, x: f32, y: f32) {
+        self.sdl.mouse().warp_mouse_in_window(&self.window, x, y);
+    }
+
+    pub fn set_cursor(&mut self, cursor: input::Cursor) {
+        cursor.set();
+        self.cursor = Some(cursor);
+    }
+
+    pub fn set_system_cursor(&mut self, kind: input::SystemCursor) -> Result<()> {
+        self.set_cursor(input::Cursor::system(kind)?);
+        Ok(())
+    }
+
+    // Turns on text_input_event/text_editing_event, and the on-screen keyboard on some platforms
+    pub fn start_text_input(&mut self) {
+        self.window.subsystem().text_input().start(&self.window);
+    }
+
+    pub fn stop_text_input(&mut self) {
+        self.window.subsystem().text_input().stop(&self.window);
+    }
+
+    pub fn is_text_input_active(&self) -> bool {
+        self.window.subsystem().text_input().is_active(&self.window)
+    }
+
+    // Where the text being edited is on screen, so the IME can put its candidate window next to
+    // it. cursor is the offset of the caret from x.
+    pub fn set_text_input_area(&mut self, x: i32, y: i32, width: u32, height: u32, cursor: i32) {
+        let _ = self.window.subsystem().text_input().set_rect(
+            &self.window,
+            sdl3::rect::Rect::new(x, y, width, height),
+            cursor,
+        );
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.minimized
+    }
+
+    // Timers run once per frame, after fixed updates and before update, advanced by the same dt
+    // update gets. Callbacks can use this to check on or change any timer, their own included.
+    pub fn scheduler(&mut self) -> &mut Scheduler<Engine> {
+        &mut self.scheduler
+    }
+
+    // Number of frames run_app has finished
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    // Records every dispatched event from the next poll on. Frames run with a fixed dt while
+    // recording so a replay steps exactly the same way, start before run_app to capture everything.
+    pub fn start_recording(&mut self, dt: Duration) {
+        // Leftover time in the ticker would give the first frames extra fixed steps
+        self.fixed_ticker.reset();
+        self.recorder = Some(recording::Recorder::new(self.frame_index, dt));
+    }
+
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recorder
+            .take()
+            .map(|recorder| recorder.finish(self.frame_index))
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    // Feeds the recorded events back in on the same frames they were recorded on, with the
+    // recorded dt. Real input is ignored until the replay runs out, except for quitting.
+    pub fn start_replay(&mut self, recording: Recording) {
+        self.input.release_all();
+        self.fixed_ticker.reset();
+        self.replayer = Some(recording::Replayer::new(self.frame_index, recording));
+    }
+
+    pub fn stop_replay(&mut self) {
+        self.replayer = None;
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replayer.is_some()
+    }
+
+    // Replaying takes precedence, it shouldn't matter what dt a re-recording asks for
+    fn forced_dt(&self) -> Option<Duration> {
+        self.replayer
+            .as_ref()
+            .map(|replayer| replayer.dt())
+            .or_else(|| self.recorder.as_ref().map(|recorder| recorder.dt()))
+    }
+
+    pub fn set_clear_color<T: gfx::GlColor>(&mut self, color: &T) {
+        self.clear_color = color.gl_color();
+    }
+
+    pub fn vsync(&mut self) -> bool {
+        self.vsync_mode() != VsyncMode::Off
     }
 
     pub fn set_vsync(&mut self, enabled: bool) {
-        let _ = self.window.subsystem().gl_set_swap_interval(if enabled {
-            sdl3::video::SwapInterval::VSync
+        self.set_vsync_mode(if enabled {
+            VsyncMode::On
         } else {
-            sdl3::video::SwapInterval::Immediate
+            VsyncMode::Off
         });
     }
+
+    pub fn vsync_mode(&self) -> VsyncMode {
+        self.window
+            .subsystem()
+            .gl_get_swap_interval()
+            .map(|interval| match interval {
+                sdl3::video::SwapInterval::VSync => VsyncMode::On,
+                sdl3::video::SwapInterval::LateSwapTearing => VsyncMode::Adaptive,
+                _ => VsyncMode::Off,
+            })
+            .unwrap_or(VsyncMode::Off)
+    }
+
+    pub fn set_vsync_mode(&mut self, mode: VsyncMode) {
+        let video = self.window.subsystem();
+        let result = video.gl_set_swap_interval(match mode {
+            VsyncMode::Off => sdl3::video::SwapInterval::Immediate,
+            VsyncMode::On => sdl3::video::SwapInterval::VSync,
+            VsyncMode::Adaptive => sdl3::video::SwapInterval::LateSwapTearing,
+        });
+
+        if result.is_err() && mode == VsyncMode::Adaptive {
+            log::warn!("Adaptive vsync is unsupported, falling back to regular vsync");
+            let _ = video.gl_set_swap_interval(sdl3::video::SwapInterval::VSync);
+        }
+    }
 }
 
-pub fn init<T>(
-    title: &str,
-    width: u32,
-    height: u32,
-    window_build_fn: T,
-) -> Result<Engine, Box<dyn std::error::Error>>
+impl Drop for Engine {
+    fn drop(&mut self) {
+        profiler::release_gl();
+    }
+}
+
+pub fn init<T>(title: &str, width: u32, height: u32, window_build_fn: T) -> Result<Engine>
 where
     T: FnOnce(&mut sdl3::video::WindowBuilder) -> &mut sdl3::video::WindowBuilder,
 {
-    colog::init();
+    init_with_config(EngineConfig::new(title, width, height).with_window(window_build_fn))
+}
 
-    let sdl_context = sdl3::init()?;
-    log::debug!("Initialized SDL3 v{}", sdl3::version::version());
+// Uses SDL's offscreen video driver and renders into a framebuffer object instead of a window, so
+// this works without a display (e.g. in CI with Mesa's llvmpipe)
+pub fn init_headless(width: u32, height: u32) -> Result<Engine> {
+    init_with_config(EngineConfig::new("baphomet (headless)", width, height).with_headless(true))
+}
+
+pub fn init_with_config(config: EngineConfig<'_>) -> Result<Engine> {
+    if config.install_logger {
+        // Tests initialize the engine repeatedly, only the first logger wins anyway
+        let _ = colog::default_builder().try_init();
+    }
 
-    let video_subsystem = sdl_context.video()?;
+    // The driver hint is process wide, so it's only set while the video subsystem picks a driver.
+    // Otherwise a later windowed init in the same process would silently stay offscreen.
+    let previous_driver = config.headless.then(|| {
+        let previous = sdl3::hint::get("SDL_VIDEO_DRIVER");
+        sdl3::hint::set("SDL_VIDEO_DRIVER", "offscreen");
+        previous
+    });
+    let video = sdl3::init().and_then(|sdl_context| {
+        let video_subsystem = sdl_context.video()?;
+        Ok((sdl_context, video_subsystem))
+    });
+    match previous_driver {
+        Some(Some(previous)) => {
+            sdl3::hint::set("SDL_VIDEO_DRIVER", &previous);
+        }
+        Some(None) => unsafe {
+            sdl3::sys::hints::SDL_ResetHint(c"SDL_VIDEO_DRIVER".as_ptr());
+        },
+        None => {}
+    }
+
+    let (sdl_context, video_subsystem) = video.map_err(|e| Error::SdlInit(e.to_string()))?;
+    log::debug!("Initialized SDL3 v{}", sdl3::version::version());
+    let gamepad_subsystem = sdl_context
+        .gamepad()
+        .map_err(|e| Error::SdlInit(e.to_string()))?;
+    let event_pump = sdl_context
+        .event_pump()
+        .map_err(|e| Error::SdlInit(e.to_string()))?;
 
     let gl_attr = video_subsystem.gl_attr();
-    gl_attr.set_context_profile(sdl3::video::GLProfile::Core);
-    gl_attr.set_context_version(3, 3);
-    #[cfg(debug_assertions)]
-    {
+    gl_attr.set_context_profile(match config.gl_profile {
+        GlProfile::Core => sdl3::video::GLProfile::Core,
+        GlProfile::Compatibility => sdl3::video::GLProfile::Compatibility,
+    });
+    gl_attr.set_context_version(config.gl_version.0, config.gl_version.1);
+    if config.debug_context {
         gl_attr.set_context_flags().debug().set();
     }
-    // gl_attr.set_multisample_buffers(1);
-    // gl_attr.set_multisample_samples(4);
+    if config.msaa_samples > 0 && !config.headless {
+        gl_attr.set_multisample_buffers(1);
+        gl_attr.set_multisample_samples(config.msaa_samples);
+    }
 
-    let mut builder = video_subsystem.window(title, width, height);
-    let window = window_build_fn(&mut builder)
+    let mut builder = video_subsystem.window(&config.title, config.width, config.height);
+    if config.headless {
+        builder.hidden();
+    }
+    if let Some(window_build_fn) = config.window_build_fn {
+        window_build_fn(&mut builder);
+    }
+    let window = builder
         .high_pixel_density()
         .opengl()
-        .build()?;
+        .build()
+        .map_err(|e| Error::WindowCreation(e.to_string()))?;
     log::debug!(
         "Opened window (size: {:?}, pixel size: {:?}, display_scale: {})",
         window.size(),
@@ -101,22 +540,32 @@ where
         window.display_scale(),
     );
 
-    let ctx = window.gl_create_context()?;
-    window.gl_make_current(&ctx)?;
+    let ctx = window
+        .gl_create_context()
+        .map_err(|e| Error::GlContext(e.to_string()))?;
+    window
+        .gl_make_current(&ctx)
+        .map_err(|e| Error::GlContext(e.to_string()))?;
 
     gl::load_with(|name| match video_subsystem.gl_get_proc_address(name) {
         None => std::ptr::null(),
         Some(addr) => addr as *const _,
     });
 
-    #[cfg(debug_assertions)]
-    unsafe {
-        gl::Enable(gl::DEBUG_OUTPUT);
-        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
-        gl::DebugMessageCallback(Some(gl_debug_callback), std::ptr::null());
+    profiler::init_gl();
+
+    if config.debug_context && gl::DebugMessageCallback::is_loaded() {
+        unsafe {
+            gl::Enable(gl::DEBUG_OUTPUT);
+            gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+            gl::DebugMessageCallback(Some(gl_debug_callback), std::ptr::null());
+        }
     }
 
     unsafe {
+        if config.msaa_samples > 0 {
+            gl::Enable(gl::MULTISAMPLE);
+        }
         gl::PointSize(window.display_scale());
         gl::LineWidth(window.display_scale());
     }
@@ -127,110 +576,371 @@ where
         gl_attr.context_version().1
     );
 
-    Ok(Engine {
+    let offscreen_target = if config.headless {
+        let (pixel_width, pixel_height) = window.size_in_pixels();
+        let fb = hlgl::Framebuffer::new(pixel_width, pixel_height)?;
+        fb.bind();
+        unsafe {
+            gl::Viewport(0, 0, pixel_width as _, pixel_height as _);
+        }
+        log::debug!(
+            "Running headless, rendering into framebuffer (id: {}, size: {:?})",
+            fb.id,
+            fb.size()
+        );
+        Some(fb)
+    } else {
+        None
+    };
+
+    let game_clock = ScaledClock::default();
+    let mut fixed_ticker =
+        Ticker::with_clock(Duration::from_secs_f64(1.0 / 60.0), game_clock.clone());
+    fixed_ticker.set_max_ticks(Some(8));
+
+    let display_scale = window.display_scale();
+    let mut engine = Engine {
         window,
         g2d: gfx::G2d::new(gl_attr.context_version()),
         frame_counter: FrameCounter::default(),
         sdl: sdl_context,
         ctx,
         running: false,
-    })
+        game_clock,
+        fixed_ticker,
+        offscreen_target,
+        clear_color: config.clear_color,
+        frame_limiter: FrameLimiter::default(),
+        focused: true,
+        minimized: false,
+        display_scale,
+        input: input::InputState::default(),
+        action_map: input::ActionMap::default(),
+        gamepads: HashMap::default(),
+        gamepad_subsystem,
+        cursor: None,
+        dropped_files: vec![],
+        frame_index: 0,
+        recorder: None,
+        replayer: None,
+        injected: VecDeque::default(),
+        scheduler: Scheduler::default(),
+        event_pump,
+    };
+    engine.set_vsync_mode(config.vsync);
+
+    Ok(engine)
 }
 
 pub fn run_app<T: Application>(engine: &mut Engine, app: &mut T) {
     engine.running = true;
 
-    // Make sure we get a good value when we ask about vsync later
-    engine.set_vsync(true);
-
-    let mut event_pump = engine.sdl.event_pump().unwrap();
+    engine.fixed_ticker.reset();
     while engine.running {
-        app.update(engine, engine.frame_counter.dt().as_secs_f32());
+        run_frame(engine, app);
+    }
+}
 
-        unsafe {
-            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+// Runs at most n frames, stopping early if the app quits. Time spent between calls isn't counted
+// towards the next frame's dt, so tests can step the loop and inspect state in between.
+pub fn run_frames<T: Application>(engine: &mut Engine, app: &mut T, n: usize) {
+    engine.running = true;
+
+    engine.fixed_ticker.reset();
+    for _ in 0..n {
+        if !engine.running {
+            break;
+        }
+        run_frame(engine, app);
+    }
+}
+
+// Shorthand for starting a replay and running the app with it
+pub fn run_replay<T: Application>(engine: &mut Engine, app: &mut T, recording: Recording) {
+    engine.start_replay(recording);
+    run_app(engine, app);
+}
+
+fn run_frame<T: Application>(engine: &mut Engine, app: &mut T) {
+    profiler::begin_frame(engine.frame_index);
+
+    let fixed_steps = match engine.forced_dt() {
+        Some(dt) => engine
+            .fixed_ticker
+            .tick_by(engine.game_clock.scale_duration(dt)),
+        None => engine.fixed_ticker.tick(),
+    };
+    let step = engine.fixed_ticker.interval().as_secs_f32();
+    if fixed_steps > 0 {
+        profile_scope!("fixed_update");
+        for _ in 0..fixed_steps {
+            app.fixed_update(engine, step);
         }
+    }
+
+    {
+        profile_scope!("scheduler");
+        run_scheduler(engine);
+    }
+
+    {
+        profile_scope!("update");
+        app.update(engine, engine.fixed_ticker.dt().as_secs_f32());
+    }
+
+    unsafe {
+        let (r, g, b, a) = engine.clear_color;
+        gl::ClearColor(r, g, b, a);
+        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+    }
 
-        app.draw(engine);
+    {
+        profile_scope!("draw");
+        app.draw(engine, engine.fixed_ticker.alpha() as f32);
+    }
 
+    {
+        profile_scope!("g2d");
         engine.g2d.draw(&engine.window_ortho_projection());
+    }
 
+    {
+        profile_scope!("swap");
         engine.window.gl_swap_window();
+    }
 
-        poll_event_pump(engine, app, &mut event_pump);
+    {
+        profile_scope!("events");
+        poll_event_pump(engine, app);
+    }
+    engine.frame_index += 1;
 
-        let _ = engine.frame_counter.update();
+    {
+        profile_scope!("wait");
+        let waited = engine.frame_limiter.wait(engine.focused);
+        engine.frame_counter.record_wait(waited);
     }
+    let _ = engine.frame_counter.update();
+
+    profiler::end_frame();
 }
 
-fn poll_event_pump<T: Application>(engine: &mut Engine, app: &mut T, event_pump: &mut EventPump) {
-    use sdl3::event::{Event, WindowEvent};
+fn run_scheduler(engine: &mut Engine) {
+    let dt = engine.fixed_ticker.dt();
+    Scheduler::update_within(engine, dt, |engine| &mut engine.scheduler);
+}
+
+fn poll_event_pump<T: Application>(engine: &mut Engine, app: &mut T) {
+    engine.input.begin_frame();
+
+    // Paired with whether the event came from SDL, replayed and injected ones don't have real
+    // devices behind them
+    let mut events = vec![];
+    while let Some(event) = Event::poll(&mut engine.event_pump) {
+        events.extend(event.map(|event| (event, true)));
+    }
+
+    if let Some(replayer) = &mut engine.replayer {
+        // Still let the window be closed mid-replay
+        events.retain(|(event, _)| *event == Event::Quit);
+        events.extend(
+            replayer
+                .take_frame(engine.frame_index)
+                .into_iter()
+                .map(|event| (event, false)),
+        );
+
+        if replayer.is_finished(engine.frame_index) {
+            log::info!("Replay finished (frame: {})", engine.frame_index);
+            engine.replayer = None;
+        }
+    }
+
+    events.extend(engine.injected.drain(..).map(|event| (event, false)));
+
+    for (event, from_sdl) in events {
+        if let Some(recorder) = &mut engine.recorder {
+            recorder.record(engine.frame_index, &event);
+        }
+
+        dispatch_event(engine, app, event, from_sdl);
+        if !engine.running {
+            break;
+        }
+    }
+}
 
-    for event in event_pump.poll_iter() {
-        match event {
-            Event::Quit { .. } => {
+fn dispatch_event<T: Application>(engine: &mut Engine, app: &mut T, event: Event, from_sdl: bool) {
+    match event {
+        Event::Quit => {
+            if app.on_quit_requested(engine) {
                 engine.running = false;
-                break;
             }
+        }
+
+        Event::Key {
+            keycode,
+            scancode,
+            keymod,
+            action,
+        } => {
+            if let Some(scancode) = scancode {
+                match action {
+                    input::KeyAction::Release => engine.input.key_up(scancode, keymod),
+                    _ => engine.input.key_down(scancode, keymod),
+                }
+            }
+            app.key_event(engine, keycode, scancode, keymod, action);
+        }
+
+        Event::MouseButton {
+            x,
+            y,
+            button,
+            clicks,
+            action,
+        } => {
+            match action {
+                input::MouseAction::Press => engine.input.mouse_button_down(button, x, y),
+                input::MouseAction::Release => engine.input.mouse_button_up(button, x, y),
+            }
+            app.mouse_button_event(engine, x, y, button, clicks, action);
+        }
+
+        Event::MouseMotion { x, y, dx, dy } => {
+            engine.input.mouse_motion(x, y, dx, dy);
+            app.mouse_motion_event(engine, x, y, dx, dy);
+        }
+
+        Event::MouseWheel {
+            x,
+            y,
+            scroll_x,
+            scroll_y,
+        } => {
+            engine.input.mouse_wheel(scroll_x, scroll_y);
+            app.mouse_wheel_event(engine, x, y, scroll_x, scroll_y);
+        }
+
+        Event::TextInput { text } => app.text_input_event(engine, &text),
+
+        Event::TextEditing {
+            text,
+            start,
+            length,
+        } => app.text_editing_event(engine, &text, start, length),
+
+        Event::DropBegin => {
+            engine.dropped_files.clear();
+            app.drop_begin_event(engine);
+        }
+
+        Event::DropFile { path, x, y } => {
+            app.file_dropped_event(engine, &path, x, y);
+            engine.dropped_files.push(path);
+        }
+
+        Event::DropText { text, x, y } => app.text_dropped_event(engine, &text, x, y),
+
+        Event::DropComplete => {
+            let files = std::mem::take(&mut engine.dropped_files);
+            app.drop_complete_event(engine, &files);
+        }
+
+        Event::GamepadAdded { id } => {
+            // Replayed and injected gamepads only exist in the input state, whatever device has
+            // their id right now has nothing to do with them
+            if from_sdl {
+                match engine.gamepad_subsystem.open(id) {
+                    Ok(pad) => {
+                        log::debug!("Opened gamepad (id: {}): {:?}", id, pad.name());
+                        engine.gamepads.insert(id, pad);
+                    }
+                    Err(e) => log::warn!("Failed to open gamepad (id: {}): {}", id, e),
+                }
+            }
+            engine.input.gamepad_added(id);
+            app.gamepad_connected_event(engine, id);
+        }
+
+        Event::GamepadRemoved { id } => {
+            if engine.gamepads.remove(&id).is_some() {
+                log::debug!("Closed gamepad (id: {})", id);
+            }
+            if engine.input.gamepad(id).is_some() {
+                engine.input.gamepad_removed(id);
+                app.gamepad_disconnected_event(engine, id);
+            }
+        }
 
-            Event::KeyDown {
-                keycode,
-                scancode,
-                keymod,
-                repeat,
-                ..
-            } => {
-                app.key_event(
-                    engine,
-                    keycode,
-                    scancode,
-                    keymod,
-                    if repeat {
-                        input::KeyAction::Repeat
-                    } else {
-                        input::KeyAction::Press
-                    },
-                );
+        Event::GamepadButton { id, button, action } => {
+            match action {
+                input::GamepadAction::Press => engine.input.gamepad_button_down(id, button),
+                input::GamepadAction::Release => engine.input.gamepad_button_up(id, button),
             }
+            app.gamepad_button_event(engine, id, button, action);
+        }
 
-            Event::KeyUp {
-                keycode,
-                scancode,
-                keymod,
-                ..
-            } => {
-                app.key_event(engine, keycode, scancode, keymod, input::KeyAction::Release);
+        Event::GamepadAxis { id, axis, value } => {
+            if let Some(value) = engine.input.gamepad_axis(id, axis, value) {
+                app.gamepad_axis_event(engine, id, axis, value);
             }
+        }
+
+        Event::WindowResized { width, height } => app.window_resize_event(engine, width, height),
 
-            Event::MouseButtonDown {
-                x, y, mouse_btn, ..
-            } => app.mouse_button_event(
-                engine,
-                x,
-                y,
-                input::MouseButton::from(mouse_btn),
-                input::MouseAction::Press,
-            ),
-
-            Event::Window {
-                win_event: WindowEvent::PixelSizeChanged(width, height),
-                ..
-            } => unsafe {
+        Event::WindowPixelResized { width, height } => {
+            unsafe {
                 gl::Viewport(0, 0, width as _, height as _);
-                gl::PointSize(engine.window.display_scale());
-                gl::LineWidth(engine.window.display_scale());
-            },
+            }
+            check_display_scale(engine, app);
+            app.window_pixel_resize_event(engine, width, height);
+        }
+
+        Event::DisplayChanged => check_display_scale(engine, app),
+
+        Event::WindowFocus { focused } => {
+            engine.focused = focused;
+            if !focused {
+                engine.input.release_all();
+            }
+            app.window_focus_event(engine, focused);
+        }
+
+        Event::WindowMinimized => {
+            engine.minimized = true;
+            app.window_minimize_event(engine, true);
+        }
 
-            _ => {}
+        Event::WindowRestored => {
+            if engine.minimized {
+                engine.minimized = false;
+                app.window_minimize_event(engine, false);
+            }
+        }
+
+        Event::WindowMoved { x, y } => app.window_move_event(engine, x, y),
+
+        Event::MouseEnter { entered } => app.mouse_enter_event(engine, entered),
+    }
+}
+
+// SDL doesn't reliably tell us about scale changes on their own, but they always come with a
+// pixel size or display change
+fn check_display_scale<T: Application>(engine: &mut Engine, app: &mut T) {
+    let scale = engine.window.display_scale();
+    if scale != engine.display_scale {
+        engine.display_scale = scale;
+        unsafe {
+            gl::PointSize(scale);
+            gl::LineWidth(scale);
         }
+        app.display_scale_event(engine, scale);
     }
 }
 
-#[cfg(debug_assertions)]
 use crate::gl::types::{GLchar, GLenum, GLsizei, GLuint, GLvoid};
 
-#[cfg(debug_assertions)]
 extern "system" fn gl_debug_callback(
     source: GLenum,
     gltype: GLenum,
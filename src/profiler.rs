@@ -0,0 +1,502 @@
+use crate::Result;
+use crate::hlgl::TimerQuery;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// Per-thread CPU scope timings and GPU timer queries, grouped into frames by run_app. Disabled by
+// default, scopes cost next to nothing until it's turned on.
+
+const DEFAULT_HISTORY_LEN: usize = 300;
+
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::profiler::CpuScope::new($name);
+    };
+}
+
+// GPU scopes can't nest (GL only allows one active GL_TIME_ELAPSED query), inner ones are skipped
+#[macro_export]
+macro_rules! profile_gpu_scope {
+    ($name:expr) => {
+        let _profile_gpu_scope = $crate::profiler::GpuScope::new($name);
+    };
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScopeRecord {
+    pub name: &'static str,
+    // Relative to when the profiler was first used on this thread
+    pub start: Duration,
+    pub duration: Duration,
+    pub depth: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameProfile {
+    pub index: u64,
+    pub start: Duration,
+    pub duration: Duration,
+    pub cpu: Vec<ScopeRecord>,
+    // Placed at the CPU time the commands were issued, the GPU actually runs somewhat behind that
+    pub gpu: Vec<ScopeRecord>,
+    gpu_pending: usize,
+}
+
+impl FrameProfile {
+    // Summed over every scope with that name
+    pub fn cpu_time(&self, name: &str) -> Duration {
+        self.cpu
+            .iter()
+            .filter(|r| r.name == name)
+            .map(|r| r.duration)
+            .sum()
+    }
+
+    pub fn gpu_time(&self, name: &str) -> Duration {
+        self.gpu
+            .iter()
+            .filter(|r| r.name == name)
+            .map(|r| r.duration)
+            .sum()
+    }
+
+    // GPU results come back a few frames late, until then gpu is missing entries
+    pub fn is_complete(&self) -> bool {
+        self.gpu_pending == 0
+    }
+
+    // Total time and number of calls per CPU scope name, in order of first use
+    pub fn cpu_summary(&self) -> Vec<(&'static str, Duration, usize)> {
+        let mut summary: Vec<(&'static str, Duration, usize)> = vec![];
+        for record in &self.cpu {
+            match summary.iter_mut().find(|(name, _, _)| *name == record.name) {
+                Some((_, total, count)) => {
+                    *total += record.duration;
+                    *count += 1;
+                }
+                None => summary.push((record.name, record.duration, 1)),
+            }
+        }
+        summary
+    }
+}
+
+struct PendingGpuScope {
+    query: TimerQuery,
+    frame: u64,
+    name: &'static str,
+    start: Duration,
+}
+
+struct Profiler {
+    enabled: bool,
+    epoch: Instant,
+    depth: u32,
+    current: Option<FrameProfile>,
+    history: VecDeque<FrameProfile>,
+    history_len: usize,
+    // Set once the GL context is up, GPU scopes do nothing without timer queries
+    gpu_supported: bool,
+    gpu_active: bool,
+    pending_gpu: Vec<PendingGpuScope>,
+    free_queries: Vec<TimerQuery>,
+}
+
+impl Profiler {
+    fn new() -> Self {
+        Self {
+            enabled: false,
+            epoch: Instant::now(),
+            depth: 0,
+            current: None,
+            history: VecDeque::default(),
+            history_len: DEFAULT_HISTORY_LEN,
+            gpu_supported: false,
+            gpu_active: false,
+            pending_gpu: vec![],
+            free_queries: vec![],
+        }
+    }
+
+    fn now(&self) -> Duration {
+        self.epoch.elapsed()
+    }
+
+    fn recording(&self) -> bool {
+        self.enabled && self.current.is_some()
+    }
+
+    fn frame_mut(&mut self, index: u64) -> Option<&mut FrameProfile> {
+        match &mut self.current {
+            Some(frame) if frame.index == index => Some(frame),
+            _ => self.history.iter_mut().find(|frame| frame.index == index),
+        }
+    }
+
+    fn collect_gpu_results(&mut self) {
+        let mut i = 0;
+        while i < self.pending_gpu.len() {
+            if !self.pending_gpu[i].query.is_available() {
+                i += 1;
+                continue;
+            }
+
+            let pending = self.pending_gpu.swap_remove(i);
+            let duration = pending.query.elapsed();
+            // The frame may have been dropped from the history already
+            if let Some(frame) = self.frame_mut(pending.frame) {
+                frame.gpu.push(ScopeRecord {
+                    name: pending.name,
+                    start: pending.start,
+                    duration,
+                    depth: 0,
+                });
+                frame.gpu.sort_by_key(|r| r.start);
+                frame.gpu_pending -= 1;
+            }
+            self.free_queries.push(pending.query);
+        }
+    }
+}
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::new());
+}
+
+pub fn set_enabled(enabled: bool) {
+    PROFILER.with_borrow_mut(|p| p.enabled = enabled);
+}
+
+pub fn is_enabled() -> bool {
+    PROFILER.with_borrow(|p| p.enabled)
+}
+
+// Number of frames kept, older ones are dropped
+pub fn set_history_len(len: usize) {
+    PROFILER.with_borrow_mut(|p| {
+        p.history_len = len.max(1);
+        while p.history.len() > p.history_len {
+            p.history.pop_front();
+        }
+    });
+}
+
+// Oldest first, the last few may still be waiting on GPU results
+pub fn frames() -> Vec<FrameProfile> {
+    PROFILER.with_borrow(|p| p.history.iter().cloned().collect())
+}
+
+pub fn last_complete_frame() -> Option<FrameProfile> {
+    PROFILER.with_borrow(|p| p.history.iter().rev().find(|f| f.is_complete()).cloned())
+}
+
+pub fn clear() {
+    PROFILER.with_borrow_mut(|p| p.history.clear());
+}
+
+pub(crate) fn begin_frame(index: u64) {
+    PROFILER.with_borrow_mut(|p| {
+        if p.enabled {
+            let start = p.now();
+            p.current = Some(FrameProfile {
+                index,
+                start,
+                ..Default::default()
+            });
+        }
+    });
+}
+
+pub(crate) fn end_frame() {
+    PROFILER.with_borrow_mut(|p| {
+        if let Some(mut frame) = p.current.take() {
+            frame.duration = p.now() - frame.start;
+            // Scopes are recorded as they close, so children come before their parents
+            frame.cpu.sort_by_key(|r| (r.start, r.depth));
+
+            if p.history.len() == p.history_len {
+                p.history.pop_front();
+            }
+            p.history.push_back(frame);
+        }
+
+        if !p.pending_gpu.is_empty() {
+            p.collect_gpu_results();
+        }
+    });
+}
+
+pub(crate) fn init_gl() {
+    let supported = TimerQuery::is_supported();
+    if !supported {
+        log::debug!("Timer queries aren't supported, GPU profiler scopes are disabled");
+    }
+    PROFILER.with_borrow_mut(|p| p.gpu_supported = supported);
+}
+
+// Queries have to be deleted while the GL context is still around, thread locals are destroyed
+// too late for that
+pub(crate) fn release_gl() {
+    PROFILER.with_borrow_mut(|p| {
+        for pending in p.pending_gpu.drain(..) {
+            if let Some(frame) = p
+                .current
+                .iter_mut()
+                .chain(p.history.iter_mut())
+                .find(|frame| frame.index == pending.frame)
+            {
+                frame.gpu_pending -= 1;
+            }
+        }
+        p.free_queries.clear();
+        p.gpu_supported = false;
+        p.gpu_active = false;
+    });
+}
+
+pub struct CpuScope {
+    name: &'static str,
+    start: Option<Duration>,
+    depth: u32,
+}
+
+impl CpuScope {
+    pub fn new(name: &'static str) -> Self {
+        PROFILER.with_borrow_mut(|p| {
+            if !p.recording() {
+                return Self {
+                    name,
+                    start: None,
+                    depth: 0,
+                };
+            }
+
+            let depth = p.depth;
+            p.depth += 1;
+            Self {
+                name,
+                start: Some(p.now()),
+                depth,
+            }
+        })
+    }
+}
+
+impl Drop for CpuScope {
+    fn drop(&mut self) {
+        let Some(start) = self.start else {
+            return;
+        };
+
+        PROFILER.with_borrow_mut(|p| {
+            p.depth = p.depth.saturating_sub(1);
+            let duration = p.now() - start;
+            // The frame can end while a scope is open, e.g. one wrapping run_app
+            if let Some(frame) = &mut p.current {
+                frame.cpu.push(ScopeRecord {
+                    name: self.name,
+                    start,
+                    duration,
+                    depth: self.depth,
+                });
+            }
+        });
+    }
+}
+
+pub struct GpuScope {
+    name: &'static str,
+    start: Duration,
+    query: Option<TimerQuery>,
+}
+
+impl GpuScope {
+    pub fn new(name: &'static str) -> Self {
+        PROFILER.with_borrow_mut(|p| {
+            let start = p.now();
+            if !p.recording() || !p.gpu_supported || p.gpu_active {
+                return Self {
+                    name,
+                    start,
+                    query: None,
+                };
+            }
+
+            let query = p.free_queries.pop().unwrap_or_default();
+            query.begin();
+            p.gpu_active = true;
+            Self {
+                name,
+                start,
+                query: Some(query),
+            }
+        })
+    }
+}
+
+impl Drop for GpuScope {
+    fn drop(&mut self) {
+        let Some(query) = self.query.take() else {
+            return;
+        };
+
+        query.end();
+        PROFILER.with_borrow_mut(|p| {
+            p.gpu_active = false;
+            match &mut p.current {
+                Some(frame) => {
+                    frame.gpu_pending += 1;
+                    let frame = frame.index;
+                    p.pending_gpu.push(PendingGpuScope {
+                        query,
+                        frame,
+                        name: self.name,
+                        start: self.start,
+                    });
+                }
+                None => p.free_queries.push(query),
+            }
+        });
+    }
+}
+
+// Chrome trace event format, loads in Perfetto and chrome://tracing. CPU scopes go on one track,
+// GPU scopes on another, with every frame in the history as an enclosing "frame" event.
+pub fn write_chrome_trace<W: Write>(w: &mut W) -> std::io::Result<()> {
+    let frames = frames();
+
+    let mut events = vec![
+        r#"{"name":"thread_name","ph":"M","pid":1,"tid":1,"args":{"name":"CPU"}}"#.to_string(),
+        r#"{"name":"thread_name","ph":"M","pid":1,"tid":2,"args":{"name":"GPU"}}"#.to_string(),
+    ];
+    for frame in &frames {
+        events.push(format!(
+            r#"{{"name":"frame","cat":"frame","ph":"X","ts":{},"dur":{},"pid":1,"tid":1,"args":{{"index":{}}}}}"#,
+            micros(frame.start),
+            micros(frame.duration),
+            frame.index
+        ));
+        for (records, cat, tid) in [(&frame.cpu, "cpu", 1), (&frame.gpu, "gpu", 2)] {
+            for record in records {
+                events.push(format!(
+                    r#"{{"name":"{}","cat":"{}","ph":"X","ts":{},"dur":{},"pid":1,"tid":{}}}"#,
+                    json_escape(record.name),
+                    cat,
+                    micros(record.start),
+                    micros(record.duration),
+                    tid
+                ));
+            }
+        }
+    }
+
+    writeln!(w, r#"{{"displayTimeUnit":"ms","traceEvents":["#)?;
+    writeln!(w, "{}", events.join(",\n"))?;
+    writeln!(w, "]}}")
+}
+
+pub fn save_chrome_trace<P: AsRef<Path>>(path: P) -> Result<()> {
+    let mut w = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_chrome_trace(&mut w)?;
+    w.flush()?;
+    Ok(())
+}
+
+// Trace timestamps are in microseconds, fractions are allowed
+fn micros(d: Duration) -> String {
+    format!("{:.3}", d.as_nanos() as f64 / 1000.0)
+}
+
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scopes_are_grouped_into_frames() {
+        set_enabled(true);
+
+        begin_frame(7);
+        {
+            let _outer = CpuScope::new("outer");
+            let _inner = CpuScope::new("inner");
+        }
+        {
+            let _inner = CpuScope::new("inner");
+        }
+        end_frame();
+
+        // Outside of a frame, nothing is recorded
+        drop(CpuScope::new("stray"));
+
+        let frames = frames();
+        assert_eq!(frames.len(), 1);
+
+        let frame = &frames[0];
+        assert_eq!(frame.index, 7);
+        assert!(frame.is_complete());
+        let names: Vec<_> = frame.cpu.iter().map(|r| (r.name, r.depth)).collect();
+        assert_eq!(names, [("outer", 0), ("inner", 1), ("inner", 0)]);
+
+        let summary = frame.cpu_summary();
+        assert_eq!(summary.len(), 2);
+        assert_eq!((summary[0].0, summary[0].2), ("outer", 1));
+        assert_eq!((summary[1].0, summary[1].2), ("inner", 2));
+        assert!(frame.cpu_time("outer") <= frame.duration);
+    }
+
+    #[test]
+    fn disabled_records_nothing() {
+        begin_frame(0);
+        drop(CpuScope::new("scope"));
+        end_frame();
+        assert!(frames().is_empty());
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        set_enabled(true);
+        set_history_len(2);
+        for i in 0..5 {
+            begin_frame(i);
+            end_frame();
+        }
+        let indices: Vec<_> = frames().iter().map(|f| f.index).collect();
+        assert_eq!(indices, [3, 4]);
+    }
+
+    #[test]
+    fn chrome_trace() {
+        set_enabled(true);
+        begin_frame(1);
+        drop(CpuScope::new("say \"hi\""));
+        end_frame();
+
+        let mut out = vec![];
+        write_chrome_trace(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.starts_with(r#"{"displayTimeUnit":"ms","traceEvents":["#));
+        assert!(out.trim_end().ends_with("]}"));
+        assert!(out.contains(r#""name":"frame","cat":"frame","ph":"X""#));
+        assert!(out.contains(r#""args":{"index":1}"#));
+        assert!(out.contains(r#""name":"say \"hi\"","cat":"cpu","ph":"X""#));
+    }
+}
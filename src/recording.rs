@@ -0,0 +1,750 @@
+use crate::input::{
+    GamepadAction, GamepadAxis, GamepadButton, KeyAction, Keycode, Mod, MouseAction, MouseButton,
+    Scancode,
+};
+use crate::{Error, Event, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Bump the trailing digit whenever the encoding changes, old recordings won't replay the same
+const MAGIC: &[u8; 8] = b"BAPHREC3";
+
+// Events tagged with the frame they were dispatched on, relative to when recording started, plus
+// the fixed dt every frame ran with so a replay can step exactly the same way
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recording {
+    dt: Duration,
+    frame_count: u64,
+    events: Vec<(u64, Event)>,
+}
+
+impl Recording {
+    pub fn new(dt: Duration) -> Self {
+        Self {
+            dt,
+            frame_count: 0,
+            events: vec![],
+        }
+    }
+
+    pub fn dt(&self) -> Duration {
+        self.dt
+    }
+
+    pub fn events(&self) -> &[(u64, Event)] {
+        &self.events
+    }
+
+    // Frames have to be pushed in non-decreasing order
+    pub fn push(&mut self, frame: u64, event: Event) {
+        debug_assert!(self.events.last().is_none_or(|(last, _)| *last <= frame));
+        self.frame_count = self.frame_count.max(frame + 1);
+        self.events.push((frame, event));
+    }
+
+    // Number of frames recorded, including idle ones after the last event
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    // Extends the recording with idle frames, never shortens it
+    pub fn set_frame_count(&mut self, frame_count: u64) {
+        self.frame_count = self.frame_count.max(frame_count);
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::decode(&std::fs::read(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, self.encode())?;
+        Ok(())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer::default();
+        w.bytes(MAGIC);
+        w.u64(self.dt.as_nanos() as u64);
+        w.varint(self.frame_count);
+        w.varint(self.events.len() as u64);
+
+        // Frames are stored as the gap from the previous event, which is almost always tiny
+        let mut prev_frame = 0;
+        for (frame, event) in &self.events {
+            w.varint(frame - prev_frame);
+            prev_frame = *frame;
+            encode_event(&mut w, event);
+        }
+
+        w.buf
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(MAGIC.len())? != MAGIC {
+            return Err(Error::RecordingFormat("not a recording file".to_string()));
+        }
+
+        let dt = Duration::from_nanos(r.u64()?);
+        let frame_count = r.varint()?;
+        let count = r.varint()?;
+
+        // Don't trust the count for the allocation, a corrupt file could claim anything
+        let mut events = Vec::with_capacity(count.min(4096) as usize);
+        let mut frame = 0u64;
+        for _ in 0..count {
+            frame = frame
+                .checked_add(r.varint()?)
+                .ok_or_else(|| Error::RecordingFormat("frame index overflow".to_string()))?;
+            events.push((frame, decode_event(&mut r)?));
+        }
+        if events
+            .last()
+            .is_some_and(|(frame, _)| *frame >= frame_count)
+        {
+            return Err(Error::RecordingFormat(format!(
+                "event on frame {frame} past the frame count {frame_count}"
+            )));
+        }
+
+        if r.pos != bytes.len() {
+            return Err(Error::RecordingFormat(format!(
+                "{} trailing bytes",
+                bytes.len() - r.pos
+            )));
+        }
+
+        Ok(Self {
+            dt,
+            frame_count,
+            events,
+        })
+    }
+}
+
+pub(crate) struct Recorder {
+    start_frame: u64,
+    recording: Recording,
+}
+
+impl Recorder {
+    pub(crate) fn new(start_frame: u64, dt: Duration) -> Self {
+        Self {
+            start_frame,
+            recording: Recording::new(dt),
+        }
+    }
+
+    pub(crate) fn dt(&self) -> Duration {
+        self.recording.dt
+    }
+
+    pub(crate) fn record(&mut self, frame: u64, event: &Event) {
+        self.recording.push(frame - self.start_frame, event.clone());
+    }
+
+    // The given frame is the first one that isn't part of the recording
+    pub(crate) fn finish(mut self, frame: u64) -> Recording {
+        self.recording.set_frame_count(frame - self.start_frame);
+        self.recording
+    }
+}
+
+pub(crate) struct Replayer {
+    start_frame: u64,
+    recording: Recording,
+    next: usize,
+}
+
+impl Replayer {
+    pub(crate) fn new(start_frame: u64, recording: Recording) -> Self {
+        Self {
+            start_frame,
+            recording,
+            next: 0,
+        }
+    }
+
+    pub(crate) fn dt(&self) -> Duration {
+        self.recording.dt
+    }
+
+    // Everything recorded up to and including the given frame that hasn't been handed out yet
+    pub(crate) fn take_frame(&mut self, frame: u64) -> Vec<Event> {
+        let frame = frame - self.start_frame;
+        let start = self.next;
+        while self
+            .recording
+            .events
+            .get(self.next)
+            .is_some_and(|(f, _)| *f <= frame)
+        {
+            self.next += 1;
+        }
+        self.recording.events[start..self.next]
+            .iter()
+            .map(|(_, event)| event.clone())
+            .collect()
+    }
+
+    // True once every recorded frame has run, idle ones included, so the frames after the last
+    // event still step with the recorded dt
+    pub(crate) fn is_finished(&self, frame: u64) -> bool {
+        frame - self.start_frame >= self.recording.frame_count
+    }
+}
+
+const MOUSE_BUTTONS: &[MouseButton] = &[
+    MouseButton::Unknown,
+    MouseButton::Left,
+    MouseButton::Middle,
+    MouseButton::Right,
+    MouseButton::X1,
+    MouseButton::X2,
+];
+
+const MOUSE_ACTIONS: &[MouseAction] = &[MouseAction::Press, MouseAction::Release];
+
+const KEY_ACTIONS: &[KeyAction] = &[KeyAction::Press, KeyAction::Release, KeyAction::Repeat];
+
+const GAMEPAD_BUTTONS: &[GamepadButton] = &[
+    GamepadButton::Unknown,
+    GamepadButton::South,
+    GamepadButton::East,
+    GamepadButton::West,
+    GamepadButton::North,
+    GamepadButton::Back,
+    GamepadButton::Guide,
+    GamepadButton::Start,
+    GamepadButton::LeftStick,
+    GamepadButton::RightStick,
+    GamepadButton::LeftShoulder,
+    GamepadButton::RightShoulder,
+    GamepadButton::DPadUp,
+    GamepadButton::DPadDown,
+    GamepadButton::DPadLeft,
+    GamepadButton::DPadRight,
+    GamepadButton::Misc1,
+    GamepadButton::Touchpad,
+];
+
+const GAMEPAD_AXES: &[GamepadAxis] = &[
+    GamepadAxis::LeftX,
+    GamepadAxis::LeftY,
+    GamepadAxis::RightX,
+    GamepadAxis::RightY,
+    GamepadAxis::TriggerLeft,
+    GamepadAxis::TriggerRight,
+];
+
+const GAMEPAD_ACTIONS: &[GamepadAction] = &[GamepadAction::Press, GamepadAction::Release];
+
+fn encode_event(w: &mut Writer, event: &Event) {
+    match event {
+        Event::Quit => w.u8(0),
+
+        Event::Key {
+            keycode,
+            scancode,
+            keymod,
+            action,
+        } => {
+            w.u8(1);
+            // Stored as SDL's raw values, which are fixed: scancodes are USB HID usage IDs and
+            // keycodes are code points or scancodes with bit 30 set. Names change between SDL
+            // versions and not every key has one.
+            w.varint(keycode.map_or(0, |k| *k as u32 as u64 + 1));
+            w.varint(scancode.map_or(0, |s| s as i32 as u32 as u64 + 1));
+            w.u16(keymod.bits());
+            w.table(KEY_ACTIONS, action);
+        }
+
+        Event::MouseButton {
+            x,
+            y,
+            button,
+            clicks,
+            action,
+        } => {
+            w.u8(2);
+            w.f32(*x);
+            w.f32(*y);
+            w.table(MOUSE_BUTTONS, button);
+            w.u8(*clicks);
+            w.table(MOUSE_ACTIONS, action);
+        }
+
+        Event::MouseMotion { x, y, dx, dy } => {
+            w.u8(3);
+            w.f32(*x);
+            w.f32(*y);
+            w.f32(*dx);
+            w.f32(*dy);
+        }
+
+        Event::MouseWheel {
+            x,
+            y,
+            scroll_x,
+            scroll_y,
+        } => {
+            w.u8(4);
+            w.f32(*x);
+            w.f32(*y);
+            w.f32(*scroll_x);
+            w.f32(*scroll_y);
+        }
+
+        Event::TextInput { text } => {
+            w.u8(5);
+            w.str(text);
+        }
+
+        Event::TextEditing {
+            text,
+            start,
+            length,
+        } => {
+            w.u8(6);
+            w.str(text);
+            w.i32(*start);
+            w.i32(*length);
+        }
+
+        Event::DropBegin => w.u8(7),
+
+        Event::DropFile { path, x, y } => {
+            w.u8(8);
+            w.str(&path.to_string_lossy());
+            w.f32(*x);
+            w.f32(*y);
+        }
+
+        Event::DropText { text, x, y } => {
+            w.u8(9);
+            w.str(text);
+            w.f32(*x);
+            w.f32(*y);
+        }
+
+        Event::DropComplete => w.u8(10),
+
+        Event::GamepadAdded { id } => {
+            w.u8(11);
+            w.u32(*id);
+        }
+
+        Event::GamepadRemoved { id } => {
+            w.u8(12);
+            w.u32(*id);
+        }
+
+        Event::GamepadButton { id, button, action } => {
+            w.u8(13);
+            w.u32(*id);
+            w.table(GAMEPAD_BUTTONS, button);
+            w.table(GAMEPAD_ACTIONS, action);
+        }
+
+        Event::GamepadAxis { id, axis, value } => {
+            w.u8(14);
+            w.u32(*id);
+            w.table(GAMEPAD_AXES, axis);
+            w.u16(*value as u16);
+        }
+
+        Event::WindowResized { width, height } => {
+            w.u8(15);
+            w.u32(*width);
+            w.u32(*height);
+        }
+
+        Event::WindowPixelResized { width, height } => {
+            w.u8(16);
+            w.u32(*width);
+            w.u32(*height);
+        }
+
+        Event::DisplayChanged => w.u8(17),
+
+        Event::WindowFocus { focused } => {
+            w.u8(18);
+            w.u8(*focused as u8);
+        }
+
+        Event::WindowMinimized => w.u8(19),
+
+        Event::WindowRestored => w.u8(20),
+
+        Event::WindowMoved { x, y } => {
+            w.u8(21);
+            w.i32(*x);
+            w.i32(*y);
+        }
+
+        Event::MouseEnter { entered } => {
+            w.u8(22);
+            w.u8(*entered as u8);
+        }
+    }
+}
+
+fn decode_event(r: &mut Reader) -> Result<Event> {
+    let tag = r.u8()?;
+    Ok(match tag {
+        0 => Event::Quit,
+
+        1 => Event::Key {
+            keycode: r.code(Keycode::from_i32, "keycode")?,
+            scancode: r.code(Scancode::from_i32, "scancode")?,
+            keymod: Mod::from_bits_truncate(r.u16()?),
+            action: r.table(KEY_ACTIONS)?,
+        },
+
+        2 => Event::MouseButton {
+            x: r.f32()?,
+            y: r.f32()?,
+            button: r.table(MOUSE_BUTTONS)?,
+            clicks: r.u8()?,
+            action: r.table(MOUSE_ACTIONS)?,
+        },
+
+        3 => Event::MouseMotion {
+            x: r.f32()?,
+            y: r.f32()?,
+            dx: r.f32()?,
+            dy: r.f32()?,
+        },
+
+        4 => Event::MouseWheel {
+            x: r.f32()?,
+            y: r.f32()?,
+            scroll_x: r.f32()?,
+            scroll_y: r.f32()?,
+        },
+
+        5 => Event::TextInput { text: r.str()? },
+
+        6 => Event::TextEditing {
+            text: r.str()?,
+            start: r.i32()?,
+            length: r.i32()?,
+        },
+
+        7 => Event::DropBegin,
+
+        8 => Event::DropFile {
+            path: PathBuf::from(r.str()?),
+            x: r.f32()?,
+            y: r.f32()?,
+        },
+
+        9 => Event::DropText {
+            text: r.str()?,
+            x: r.f32()?,
+            y: r.f32()?,
+        },
+
+        10 => Event::DropComplete,
+
+        11 => Event::GamepadAdded { id: r.u32()? },
+
+        12 => Event::GamepadRemoved { id: r.u32()? },
+
+        13 => Event::GamepadButton {
+            id: r.u32()?,
+            button: r.table(GAMEPAD_BUTTONS)?,
+            action: r.table(GAMEPAD_ACTIONS)?,
+        },
+
+        14 => Event::GamepadAxis {
+            id: r.u32()?,
+            axis: r.table(GAMEPAD_AXES)?,
+            value: r.u16()? as i16,
+        },
+
+        15 => Event::WindowResized {
+            width: r.u32()?,
+            height: r.u32()?,
+        },
+
+        16 => Event::WindowPixelResized {
+            width: r.u32()?,
+            height: r.u32()?,
+        },
+
+        17 => Event::DisplayChanged,
+
+        18 => Event::WindowFocus {
+            focused: r.u8()? != 0,
+        },
+
+        19 => Event::WindowMinimized,
+
+        20 => Event::WindowRestored,
+
+        21 => Event::WindowMoved {
+            x: r.i32()?,
+            y: r.i32()?,
+        },
+
+        22 => Event::MouseEnter {
+            entered: r.u8()? != 0,
+        },
+
+        _ => {
+            return Err(Error::RecordingFormat(format!("unknown event tag {tag}")));
+        }
+    })
+}
+
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    fn u16(&mut self, v: u16) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    fn i32(&mut self, v: i32) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    fn f32(&mut self, v: f32) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    // LEB128, 7 bits at a time with the high bit set on all but the last byte
+    fn varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.u8(v as u8 | 0x80);
+            v >>= 7;
+        }
+        self.u8(v as u8);
+    }
+
+    fn str(&mut self, s: &str) {
+        self.varint(s.len() as u64);
+        self.bytes(s.as_bytes());
+    }
+
+    fn table<T: PartialEq>(&mut self, table: &[T], v: &T) {
+        let index = table.iter().position(|t| t == v).unwrap();
+        self.u8(index as u8);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() - self.pos < n {
+            return Err(Error::RecordingFormat("unexpected end of file".to_string()));
+        }
+        let slice = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> Result<f32> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            v |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(Error::RecordingFormat("varint too long".to_string()))
+    }
+
+    fn str(&mut self) -> Result<String> {
+        let len = self.varint()? as usize;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| Error::RecordingFormat("invalid UTF-8 in string".to_string()))
+    }
+
+    // Raw SDL key values are stored one up so 0 can stand in for None
+    fn code<T>(&mut self, from_i32: fn(i32) -> Option<T>, what: &str) -> Result<Option<T>> {
+        let v = self.varint()?;
+        if v == 0 {
+            return Ok(None);
+        }
+        u32::try_from(v - 1)
+            .ok()
+            .and_then(|raw| from_i32(raw as i32))
+            .map(Some)
+            .ok_or_else(|| Error::RecordingFormat(format!("unknown {what} {}", v - 1)))
+    }
+
+    fn table<T: Copy>(&mut self, table: &[T]) -> Result<T> {
+        let index = self.u8()?;
+        table
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| Error::RecordingFormat(format!("index {index} out of range")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> Recording {
+        let mut recording = Recording::new(Duration::from_micros(16_667));
+        recording.push(
+            0,
+            Event::Key {
+                keycode: Some(Keycode::A),
+                scancode: Some(Scancode::A),
+                keymod: Mod::LSHIFTMOD,
+                action: KeyAction::Press,
+            },
+        );
+        recording.push(
+            0,
+            Event::MouseButton {
+                x: 12.5,
+                y: -3.0,
+                button: MouseButton::X2,
+                clicks: 2,
+                action: MouseAction::Release,
+            },
+        );
+        recording.push(
+            3,
+            Event::MouseWheel {
+                x: 1.0,
+                y: 2.0,
+                scroll_x: 0.0,
+                scroll_y: -1.5,
+            },
+        );
+        recording.push(
+            3,
+            Event::TextEditing {
+                text: "héllo".to_string(),
+                start: 1,
+                length: -1,
+            },
+        );
+        recording.push(
+            200,
+            Event::DropFile {
+                path: PathBuf::from("some/file.png"),
+                x: 4.0,
+                y: 5.0,
+            },
+        );
+        recording.push(
+            201,
+            Event::GamepadAxis {
+                id: 7,
+                axis: GamepadAxis::TriggerRight,
+                value: i16::MIN,
+            },
+        );
+        recording.push(
+            201,
+            Event::GamepadButton {
+                id: 7,
+                button: GamepadButton::Touchpad,
+                action: GamepadAction::Press,
+            },
+        );
+        recording.push(100_000, Event::WindowMoved { x: -20, y: 30 });
+        recording.push(100_000, Event::Quit);
+        recording
+    }
+
+    #[test]
+    fn round_trip() {
+        let recording = sample();
+        let decoded = Recording::decode(&recording.encode()).unwrap();
+        assert_eq!(decoded, recording);
+        assert_eq!(decoded.frame_count(), 100_001);
+
+        // Idle frames at the end survive too
+        let mut recording = sample();
+        recording.set_frame_count(100_050);
+        let decoded = Recording::decode(&recording.encode()).unwrap();
+        assert_eq!(decoded.frame_count(), 100_050);
+    }
+
+    #[test]
+    fn key_without_codes() {
+        let mut recording = Recording::new(Duration::from_millis(10));
+        recording.push(
+            5,
+            Event::Key {
+                keycode: None,
+                scancode: None,
+                keymod: Mod::empty(),
+                action: KeyAction::Repeat,
+            },
+        );
+        assert_eq!(Recording::decode(&recording.encode()).unwrap(), recording);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(Recording::decode(b"NOTAREC!").is_err());
+
+        let bytes = sample().encode();
+        assert!(Recording::decode(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Recording::decode(&trailing).is_err());
+    }
+}
@@ -0,0 +1,352 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+// Ids are global so a handle from one scheduler can never match a timer in another
+static NEXT_TIMER_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TimerHandle(u64);
+
+impl TimerHandle {
+    fn next() -> Self {
+        Self(NEXT_TIMER_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+enum Trigger {
+    After(Duration),
+    Every { interval: Duration, left: Duration },
+    EveryNFrames { n: u64, left: u64 },
+}
+
+struct Timer<C> {
+    handle: TimerHandle,
+    trigger: Trigger,
+    paused: bool,
+    callback: Box<dyn FnMut(&mut C)>,
+}
+
+impl Trigger {
+    // Advances by one frame of dt and returns how many times the timer is due
+    fn advance(&mut self, dt: Duration) -> u32 {
+        match self {
+            Self::After(left) => {
+                if *left > dt {
+                    *left -= dt;
+                    0
+                } else {
+                    1
+                }
+            }
+
+            Self::Every { interval, left } => {
+                if interval.is_zero() {
+                    return 1;
+                }
+
+                let mut elapsed = dt;
+                let mut count = 0;
+                while elapsed >= *left {
+                    elapsed -= *left;
+                    *left = *interval;
+                    count += 1;
+                }
+                *left -= elapsed;
+                count
+            }
+
+            Self::EveryNFrames { n, left } => {
+                *left -= 1;
+                if *left == 0 {
+                    *left = *n;
+                    1
+                } else {
+                    0
+                }
+            }
+        }
+    }
+}
+
+// Runs callbacks after a delay, on an interval or every n frames. C is whatever the callbacks get
+// mutable access to, the engine's own scheduler uses Engine.
+pub struct Scheduler<C> {
+    timers: Vec<Timer<C>>,
+    paused: bool,
+}
+
+impl<C> Default for Scheduler<C> {
+    fn default() -> Self {
+        Self {
+            timers: vec![],
+            paused: false,
+        }
+    }
+}
+
+impl<C> Scheduler<C> {
+    pub fn after<F>(&mut self, delay: Duration, callback: F) -> TimerHandle
+    where
+        F: FnOnce(&mut C) + 'static,
+    {
+        let mut callback = Some(callback);
+        self.add(
+            Trigger::After(delay),
+            Box::new(move |ctx| {
+                if let Some(callback) = callback.take() {
+                    callback(ctx);
+                }
+            }),
+        )
+    }
+
+    // Fires once per elapsed interval, so a long frame can fire it several times in a row
+    pub fn every<F>(&mut self, interval: Duration, callback: F) -> TimerHandle
+    where
+        F: FnMut(&mut C) + 'static,
+    {
+        self.add(
+            Trigger::Every {
+                interval,
+                left: interval,
+            },
+            Box::new(callback),
+        )
+    }
+
+    // Frames are calls to update, paused frames don't count
+    pub fn every_n_frames<F>(&mut self, n: u64, callback: F) -> TimerHandle
+    where
+        F: FnMut(&mut C) + 'static,
+    {
+        let n = n.max(1);
+        self.add(Trigger::EveryNFrames { n, left: n }, Box::new(callback))
+    }
+
+    fn add(&mut self, trigger: Trigger, callback: Box<dyn FnMut(&mut C)>) -> TimerHandle {
+        let handle = TimerHandle::next();
+        self.timers.push(Timer {
+            handle,
+            trigger,
+            paused: false,
+            callback,
+        });
+        handle
+    }
+
+    pub fn cancel(&mut self, handle: TimerHandle) {
+        self.timers.retain(|t| t.handle != handle);
+    }
+
+    pub fn pause(&mut self, handle: TimerHandle) {
+        if let Some(timer) = self.timer_mut(handle) {
+            timer.paused = true;
+        }
+    }
+
+    pub fn resume(&mut self, handle: TimerHandle) {
+        if let Some(timer) = self.timer_mut(handle) {
+            timer.paused = false;
+        }
+    }
+
+    // One-shot timers stop being active once they've fired
+    pub fn is_active(&self, handle: TimerHandle) -> bool {
+        self.timers.iter().any(|t| t.handle == handle)
+    }
+
+    pub fn is_timer_paused(&self, handle: TimerHandle) -> bool {
+        self.timers.iter().any(|t| t.handle == handle && t.paused)
+    }
+
+    // Pauses/resumes everything, individually paused timers stay paused on resume
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.timers.clear();
+    }
+
+    fn timer_mut(&mut self, handle: TimerHandle) -> Option<&mut Timer<C>> {
+        self.timers.iter_mut().find(|t| t.handle == handle)
+    }
+
+    // Advances every timer by dt (one frame) and fires the ones that are due, in the order they
+    // were added
+    pub fn update(&mut self, ctx: &mut C, dt: Duration) {
+        Self::run(&mut (self, ctx), dt, |x| &mut *x.0, |x| &mut *x.1);
+    }
+
+    // Like update, for a scheduler that lives inside its own context. Callbacks reach this same
+    // scheduler through the context, so they see every timer and can change any of them.
+    pub fn update_within<F>(ctx: &mut C, dt: Duration, scheduler: F)
+    where
+        F: Fn(&mut C) -> &mut Self,
+    {
+        Self::run(ctx, dt, scheduler, |ctx| ctx);
+    }
+
+    // Callbacks can add, cancel and pause timers (their own included) while this runs, so timers
+    // are looked up by handle again after every call. Timers added along the way first run on the
+    // next update.
+    fn run<X, S, T>(x: &mut X, dt: Duration, scheduler: S, ctx: T)
+    where
+        S: Fn(&mut X) -> &mut Self,
+        T: Fn(&mut X) -> &mut C,
+    {
+        let handles: Vec<_> = scheduler(x).timers.iter().map(|t| t.handle).collect();
+        for handle in handles {
+            let s = scheduler(x);
+            if s.paused {
+                return;
+            }
+            let fires = match s.timer_mut(handle) {
+                Some(timer) if !timer.paused => timer.trigger.advance(dt),
+                _ => continue,
+            };
+
+            for _ in 0..fires {
+                let s = scheduler(x);
+                if s.paused {
+                    return;
+                }
+                let Some(index) = s.timers.iter().position(|t| t.handle == handle) else {
+                    break;
+                };
+                if s.timers[index].paused {
+                    break;
+                }
+
+                // One-shot timers are gone by the time they fire, repeating ones lend out their
+                // callback for the call
+                if let Trigger::After(_) = s.timers[index].trigger {
+                    let mut timer = s.timers.remove(index);
+                    (timer.callback)(ctx(x));
+                    break;
+                }
+                let mut callback =
+                    std::mem::replace(&mut s.timers[index].callback, Box::new(|_| {}));
+                callback(ctx(x));
+                if let Some(timer) = scheduler(x).timer_mut(handle) {
+                    timer.callback = callback;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn after_fires_once() {
+        let mut scheduler = Scheduler::<Vec<&str>>::default();
+        let mut log = vec![];
+
+        let handle = scheduler.after(10 * MS, |log| log.push("after"));
+        scheduler.update(&mut log, 6 * MS);
+        assert!(log.is_empty());
+        assert!(scheduler.is_active(handle));
+
+        scheduler.update(&mut log, 4 * MS);
+        assert_eq!(log, ["after"]);
+        assert!(!scheduler.is_active(handle));
+
+        scheduler.update(&mut log, 100 * MS);
+        assert_eq!(log, ["after"]);
+    }
+
+    #[test]
+    fn every_catches_up() {
+        let mut scheduler = Scheduler::<u32>::default();
+        let mut count = 0;
+
+        scheduler.every(10 * MS, |count| *count += 1);
+        scheduler.update(&mut count, 25 * MS);
+        assert_eq!(count, 2);
+
+        // 5ms left over from the last update
+        scheduler.update(&mut count, 5 * MS);
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn every_n_frames() {
+        let mut scheduler = Scheduler::<u32>::default();
+        let mut count = 0;
+
+        scheduler.every_n_frames(3, |count| *count += 1);
+        for _ in 0..7 {
+            scheduler.update(&mut count, Duration::ZERO);
+        }
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn cancel_and_pause() {
+        let mut scheduler = Scheduler::<Vec<&str>>::default();
+        let mut log = vec![];
+
+        let a = scheduler.every(10 * MS, |log| log.push("a"));
+        let b = scheduler.every(10 * MS, |log| log.push("b"));
+        let c = scheduler.every(10 * MS, |log| log.push("c"));
+
+        scheduler.cancel(a);
+        scheduler.pause(b);
+        scheduler.update(&mut log, 10 * MS);
+        assert_eq!(log, ["c"]);
+
+        scheduler.resume(b);
+        scheduler.set_paused(true);
+        scheduler.update(&mut log, 10 * MS);
+        assert_eq!(log, ["c"]);
+
+        scheduler.set_paused(false);
+        scheduler.update(&mut log, 10 * MS);
+        assert_eq!(log, ["c", "b", "c"]);
+        assert!(!scheduler.is_active(a));
+        assert!(scheduler.is_active(c));
+    }
+
+    struct Game {
+        scheduler: Scheduler<Game>,
+        log: Vec<bool>,
+    }
+
+    #[test]
+    fn callbacks_see_their_scheduler() {
+        let mut game = Game {
+            scheduler: Scheduler::default(),
+            log: vec![],
+        };
+        let once = game.scheduler.after(20 * MS, |_| {});
+        game.scheduler.every(10 * MS, move |game: &mut Game| {
+            let active = game.scheduler.is_active(once);
+            game.log.push(active);
+        });
+
+        Scheduler::update_within(&mut game, 10 * MS, |game| &mut game.scheduler);
+        assert_eq!(game.log, [true]);
+
+        // The one-shot was added first, so it's already gone by the time the other one looks
+        Scheduler::update_within(&mut game, 10 * MS, |game| &mut game.scheduler);
+        assert_eq!(game.log, [true, false]);
+        assert_eq!(game.scheduler.len(), 1);
+    }
+}
@@ -1,13 +1,18 @@
-use crate::EMA;
+mod clock;
+
+use crate::TimeWindowedMean;
+pub use clock::*;
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
 pub struct Ticker {
-    start: Instant,
+    clock: Box<dyn Clock>,
+    start: Duration,
     interval: Duration,
-    last: Instant,
+    last: Duration,
     dt: Duration,
     acc: Duration,
+    max_ticks: Option<usize>,
 }
 
 impl Default for Ticker {
@@ -18,31 +23,103 @@ impl Default for Ticker {
 
 impl Ticker {
     pub fn new(interval: Duration) -> Self {
+        Self::with_clock(interval, RealClock::default())
+    }
+
+    pub fn with_clock<C: Clock + 'static>(interval: Duration, clock: C) -> Self {
+        let now = clock.now();
         Self {
-            start: Instant::now(),
+            clock: Box::new(clock),
+            start: now,
             interval,
-            last: Instant::now(),
+            last: now,
             dt: Duration::default(),
             acc: Duration::default(),
+            max_ticks: None,
         }
     }
+
+    // Caps the intervals reported by a single tick, time accumulated past the cap is dropped so a
+    // long stall doesn't turn into a spiral of catch-up ticks
+    pub fn set_max_ticks(&mut self, max_ticks: Option<usize>) {
+        self.max_ticks = max_ticks;
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+        self.acc = Duration::default();
+    }
+
+    // Panics unless the rate is positive and finite, a zero interval would silently stop ticking
+    pub fn set_rate(&mut self, ticks_per_second: f64) {
+        assert!(
+            ticks_per_second.is_finite() && ticks_per_second > 0.0,
+            "tick rate must be positive and finite, got {ticks_per_second}"
+        );
+        let interval = Duration::from_secs_f64(1.0 / ticks_per_second);
+        assert!(
+            !interval.is_zero(),
+            "tick rate {ticks_per_second} is too high, the interval rounds to 0"
+        );
+        self.set_interval(interval);
+    }
+
+    pub fn reset(&mut self) {
+        self.start = self.clock.now();
+        self.last = self.start;
+        self.dt = Duration::default();
+        self.acc = Duration::default();
+    }
+
     pub fn tick(&mut self) -> usize {
-        let now = Instant::now();
-        self.dt = now - self.last;
+        let now = self.clock.now();
+        let dt = now.saturating_sub(self.last);
         self.last = now;
 
+        self.advance(dt)
+    }
+
+    // Like tick, but pretends exactly dt has passed instead of measuring it
+    pub fn tick_by(&mut self, dt: Duration) -> usize {
+        self.last = self.clock.now();
+        self.advance(dt)
+    }
+
+    fn advance(&mut self, dt: Duration) -> usize {
+        self.dt = dt;
+
         let mut tick_count = 0;
         if !self.interval.is_zero() {
             self.acc += self.dt;
             while self.acc >= self.interval {
                 self.acc -= self.interval;
                 tick_count += 1;
+
+                if self.max_ticks.is_some_and(|max| tick_count >= max) {
+                    self.acc = Duration::from_nanos(
+                        (self.acc.as_nanos() % self.interval.as_nanos()) as u64,
+                    );
+                    break;
+                }
             }
         }
 
         tick_count
     }
 
+    // How far the accumulator is into the next interval, in [0, 1)
+    pub fn alpha(&self) -> f64 {
+        if self.interval.is_zero() {
+            0.0
+        } else {
+            self.acc.as_secs_f64() / self.interval.as_secs_f64()
+        }
+    }
+
     pub fn dt(&self) -> Duration {
         self.dt
     }
@@ -52,13 +129,46 @@ impl Ticker {
     }
 }
 
+const FPS_WINDOW: Duration = Duration::from_secs(1);
+
+// Frames kept for FrameStats, enough for the 99th percentile to mean something
+const DEFAULT_FRAME_HISTORY: usize = 1000;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    pub frames: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    // Average fps over the slowest 1% of frames (at least one)
+    pub low_1_percent_fps: f64,
+    // Mean absolute difference between consecutive frame times, 0 for perfectly even pacing
+    pub jitter: Duration,
+}
+
+// Frame time distribution, the last bucket also counts everything past the end
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameHistogram {
+    pub bucket_width: Duration,
+    pub counts: Vec<usize>,
+}
+
 pub struct FrameCounter {
+    clock: Box<dyn Clock>,
     #[allow(dead_code)]
-    start: Instant,
-    timestamps: VecDeque<Instant>,
-    ticker: Ticker,
-    averager: EMA,
+    start: Duration,
+    // Frame times in seconds, fps is the inverse of their mean
+    fps_window: TimeWindowedMean,
     user_ticker: Ticker,
+    pending_wait: Duration,
+    wait_time: Duration,
+    work_time: Duration,
+    last_frame: Option<Duration>,
+    frame_times: VecDeque<Duration>,
+    history_len: usize,
 }
 
 impl Default for FrameCounter {
@@ -69,42 +179,465 @@ impl Default for FrameCounter {
 
 impl FrameCounter {
     pub fn new(user_interval: Duration) -> Self {
+        Self::with_clock(user_interval, RealClock::default())
+    }
+
+    pub fn with_clock<C: Clock + Clone + 'static>(user_interval: Duration, clock: C) -> Self {
         Self {
-            start: Instant::now(),
-            timestamps: VecDeque::default(),
-            ticker: Ticker::new(Duration::from_millis(500)),
-            averager: EMA::new(1.0),
-            user_ticker: Ticker::new(user_interval),
+            start: clock.now(),
+            fps_window: TimeWindowedMean::new(FPS_WINDOW),
+            user_ticker: Ticker::with_clock(user_interval, clock.clone()),
+            clock: Box::new(clock),
+            pending_wait: Duration::default(),
+            wait_time: Duration::default(),
+            work_time: Duration::default(),
+            last_frame: None,
+            frame_times: VecDeque::with_capacity(DEFAULT_FRAME_HISTORY),
+            history_len: DEFAULT_FRAME_HISTORY,
         }
     }
 
-    pub fn update(&mut self) -> usize {
-        self.timestamps.push_back(Instant::now());
+    // Time the frame limiter slept/spun during the current frame, call before update
+    pub fn record_wait(&mut self, wait: Duration) {
+        self.pending_wait += wait;
+    }
 
-        while self.timestamps.len() > 2
-            && *self.timestamps.back().unwrap() - *self.timestamps.front().unwrap()
-                > Duration::from_secs(1)
-        {
-            self.timestamps.pop_front();
+    pub fn update(&mut self) -> usize {
+        let now = self.clock.now();
+        if let Some(last) = self.last_frame {
+            if self.frame_times.len() == self.history_len {
+                self.frame_times.pop_front();
+            }
+            let dt = now.saturating_sub(last);
+            self.frame_times.push_back(dt);
+            self.fps_window.update(now, dt.as_secs_f64());
         }
+        self.last_frame = Some(now);
 
-        self.averager.update(self.timestamps.len() as f64);
-        if self.ticker.tick() > 0 {
-            self.averager.alpha = 2.0 / (1.0 + self.timestamps.len() as f64);
-        }
+        self.wait_time = self.pending_wait;
+        self.work_time = self.dt().saturating_sub(self.wait_time);
+        self.pending_wait = Duration::default();
 
         self.user_ticker.tick()
     }
 
+    // How long the last frame spent waiting in the frame limiter
+    pub fn wait_time(&self) -> Duration {
+        self.wait_time
+    }
+
+    // How long the last frame spent on everything but the frame limiter (including vsync)
+    pub fn work_time(&self) -> Duration {
+        self.work_time
+    }
+
+    // Averaged over the last second
     pub fn fps(&self) -> f64 {
-        self.averager.value()
+        let mean = self.fps_window.value();
+        if mean > 0.0 { 1.0 / mean } else { 0.0 }
     }
 
+    // Duration of the last full frame, in real time
     pub fn dt(&self) -> Duration {
-        if self.timestamps.len() < 2 {
-            Duration::default()
+        self.frame_times.back().copied().unwrap_or_default()
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.history_len
+    }
+
+    // Number of frames kept for stats, older ones are dropped
+    pub fn set_history_len(&mut self, len: usize) {
+        self.history_len = len.max(1);
+        while self.frame_times.len() > self.history_len {
+            self.frame_times.pop_front();
+        }
+    }
+
+    // Oldest first
+    pub fn frame_times(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.frame_times.iter().copied()
+    }
+
+    // Sorts a copy of the history, so this is meant for the odd debug overlay refresh rather than
+    // every frame
+    pub fn stats(&self) -> FrameStats {
+        let n = self.frame_times.len();
+        if n == 0 {
+            return FrameStats::default();
+        }
+
+        let mut sorted: Vec<_> = self.frame_times.iter().copied().collect();
+        sorted.sort_unstable();
+
+        // Nearest rank
+        let percentile = |p: f64| sorted[((p / 100.0 * n as f64).ceil() as usize).clamp(1, n) - 1];
+
+        let total: Duration = sorted.iter().sum();
+
+        let slowest_count = n.div_ceil(100);
+        let slowest: Duration = sorted[n - slowest_count..].iter().sum();
+        let low_1_percent_fps = if slowest.is_zero() {
+            0.0
+        } else {
+            slowest_count as f64 / slowest.as_secs_f64()
+        };
+
+        let jitter = if n < 2 {
+            Duration::ZERO
         } else {
-            *self.timestamps.back().unwrap() - *self.timestamps.front().unwrap()
+            let diffs: Duration = self
+                .frame_times
+                .iter()
+                .zip(self.frame_times.iter().skip(1))
+                .map(|(a, b)| a.abs_diff(*b))
+                .sum();
+            diffs / (n - 1) as u32
+        };
+
+        FrameStats {
+            frames: n,
+            min: sorted[0],
+            max: sorted[n - 1],
+            mean: total / n as u32,
+            p50: percentile(50.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            low_1_percent_fps,
+            jitter,
+        }
+    }
+
+    pub fn histogram(&self, bucket_width: Duration, bucket_count: usize) -> FrameHistogram {
+        let mut counts = vec![0; bucket_count.max(1)];
+        let last = counts.len() - 1;
+        for dt in &self.frame_times {
+            let bucket = if bucket_width.is_zero() {
+                last
+            } else {
+                ((dt.as_nanos() / bucket_width.as_nanos()) as usize).min(last)
+            };
+            counts[bucket] += 1;
         }
+
+        FrameHistogram {
+            bucket_width,
+            counts,
+        }
+    }
+}
+
+// Sleeps most of the remaining frame time and spins for the last bit, since sleeping alone is
+// only accurate to around a millisecond (much worse on some platforms)
+const LIMITER_SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+// Always on real time, sleeping until a deadline on any other clock wouldn't mean much
+#[derive(Default)]
+pub struct FrameLimiter {
+    target_period: Option<Duration>,
+    background_period: Option<Duration>,
+    last: Option<Instant>,
+}
+
+impl FrameLimiter {
+    pub fn target_fps(&self) -> Option<f64> {
+        self.target_period.map(|p| 1.0 / p.as_secs_f64())
+    }
+
+    // Panics unless fps is positive and finite, None turns the cap off
+    pub fn set_target_fps(&mut self, fps: Option<f64>) {
+        self.target_period = fps.map(period_from_fps);
+    }
+
+    pub fn background_fps(&self) -> Option<f64> {
+        self.background_period.map(|p| 1.0 / p.as_secs_f64())
+    }
+
+    // Applies instead of the target fps while the window doesn't have focus
+    pub fn set_background_fps(&mut self, fps: Option<f64>) {
+        self.background_period = fps.map(period_from_fps);
+    }
+
+    // Blocks until a full frame period has passed since the last call and returns the time waited
+    pub fn wait(&mut self, focused: bool) -> Duration {
+        let period = if focused {
+            self.target_period
+        } else {
+            self.background_period.or(self.target_period)
+        };
+
+        let start = Instant::now();
+        let (Some(period), Some(last)) = (period, self.last) else {
+            self.last = Some(start);
+            return Duration::default();
+        };
+
+        let deadline = last + period;
+        let sleep = sleep_before(deadline, start);
+        if !sleep.is_zero() {
+            std::thread::sleep(sleep);
+        }
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+
+        let now = Instant::now();
+        self.last = Some(next_frame_start(deadline, period, now));
+
+        now - start
+    }
+}
+
+fn period_from_fps(fps: f64) -> Duration {
+    assert!(
+        fps.is_finite() && fps > 0.0,
+        "fps must be positive and finite, got {fps}"
+    );
+    Duration::from_secs_f64(1.0 / fps)
+}
+
+// How long to sleep at now before spinning the rest of the way to deadline
+fn sleep_before(deadline: Instant, now: Instant) -> Duration {
+    deadline
+        .saturating_duration_since(now)
+        .saturating_sub(LIMITER_SPIN_MARGIN)
+}
+
+// What the next frame's deadline is measured from once the wait ended at now. Stays on the
+// deadline grid unless we fell behind by a whole frame.
+fn next_frame_start(deadline: Instant, period: Duration, now: Instant) -> Instant {
+    if now.saturating_duration_since(deadline) < period {
+        deadline
+    } else {
+        now
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn assert_send<T: Send>() {}
+
+    #[test]
+    fn counters_are_send() {
+        assert_send::<Ticker>();
+        assert_send::<FrameCounter>();
+        assert_send::<FrameLimiter>();
+    }
+
+    fn counter_with_frames(frame_times_ms: &[u64]) -> FrameCounter {
+        let clock = ManualClock::new();
+        let mut counter = FrameCounter::with_clock(Duration::ZERO, clock.clone());
+        counter.update();
+        for ms in frame_times_ms {
+            clock.advance(Duration::from_millis(*ms));
+            counter.update();
+        }
+        counter
+    }
+
+    #[test]
+    fn ticker_counts_intervals() {
+        let clock = ManualClock::new();
+        let mut ticker = Ticker::with_clock(10 * MS, clock.clone());
+
+        clock.advance(25 * MS);
+        assert_eq!(ticker.tick(), 2);
+        assert_eq!(ticker.dt(), 25 * MS);
+        assert!((ticker.alpha() - 0.5).abs() < 1e-9);
+
+        clock.advance(5 * MS);
+        assert_eq!(ticker.tick(), 1);
+        assert_eq!(ticker.elapsed(), 30 * MS);
+
+        assert_eq!(ticker.tick_by(20 * MS), 2);
+        assert_eq!(ticker.dt(), 20 * MS);
+    }
+
+    #[test]
+    fn ticker_max_ticks_keeps_remainder() {
+        let clock = ManualClock::new();
+        let mut ticker = Ticker::with_clock(10 * MS, clock.clone());
+        ticker.set_max_ticks(Some(3));
+
+        clock.advance(104 * MS);
+        assert_eq!(ticker.tick(), 3);
+        assert!((ticker.alpha() - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ticker_rate() {
+        let clock = ManualClock::new();
+        let mut ticker = Ticker::with_clock(Duration::ZERO, clock.clone());
+        ticker.set_rate(50.0);
+        assert_eq!(ticker.interval(), 20 * MS);
+
+        clock.advance(100 * MS);
+        assert_eq!(ticker.tick(), 5);
+
+        for rate in [0.0, -60.0, f64::NAN, f64::INFINITY, 1e12] {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                ticker.set_rate(rate);
+            }));
+            assert!(result.is_err(), "rate {rate} was accepted");
+        }
+        assert_eq!(ticker.interval(), 20 * MS);
+    }
+
+    #[test]
+    fn ticker_follows_scaled_clock() {
+        let source = ManualClock::new();
+        let clock = ScaledClock::new(source.clone());
+        let mut ticker = Ticker::with_clock(10 * MS, clock.clone());
+
+        clock.set_scale(0.5);
+        source.advance(40 * MS);
+        assert_eq!(ticker.tick(), 2);
+
+        clock.set_paused(true);
+        source.advance(40 * MS);
+        assert_eq!(ticker.tick(), 0);
+        assert_eq!(ticker.dt(), Duration::ZERO);
+    }
+
+    #[test]
+    fn frame_counter_fps() {
+        let clock = ManualClock::new();
+        let mut counter = FrameCounter::with_clock(Duration::ZERO, clock.clone());
+        assert_eq!(counter.fps(), 0.0);
+
+        counter.update();
+        for _ in 0..200 {
+            clock.advance(10 * MS);
+            counter.update();
+        }
+        assert_relative_eq!(counter.fps(), 100.0, epsilon = 1e-9);
+
+        // A full second later only the slower frames are left
+        for _ in 0..50 {
+            clock.advance(20 * MS);
+            counter.update();
+        }
+        assert_relative_eq!(counter.fps(), 50.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn frame_counter_splits_wait_and_work() {
+        let clock = ManualClock::new();
+        let mut counter = FrameCounter::with_clock(Duration::ZERO, clock.clone());
+        counter.update();
+
+        clock.advance(16 * MS);
+        counter.record_wait(3 * MS);
+        counter.record_wait(MS);
+        counter.update();
+        assert_eq!(counter.wait_time(), 4 * MS);
+        assert_eq!(counter.work_time(), 12 * MS);
+
+        // Waits only count towards the frame they were recorded in
+        clock.advance(10 * MS);
+        counter.update();
+        assert_eq!(counter.wait_time(), Duration::ZERO);
+        assert_eq!(counter.work_time(), 10 * MS);
+    }
+
+    #[test]
+    fn frame_limiter_deadlines() {
+        let t = Instant::now();
+        assert_eq!(sleep_before(t + 10 * MS, t), 8 * MS);
+        assert_eq!(sleep_before(t + MS, t), Duration::ZERO);
+        assert_eq!(sleep_before(t, t + MS), Duration::ZERO);
+
+        // A little late stays on the grid, a whole frame late starts over from now
+        assert_eq!(next_frame_start(t, 10 * MS, t + 3 * MS), t);
+        assert_eq!(next_frame_start(t, 10 * MS, t + 12 * MS), t + 12 * MS);
+    }
+
+    #[test]
+    fn frame_limiter_waits_a_period() {
+        let mut limiter = FrameLimiter::default();
+        assert_eq!(limiter.wait(true), Duration::ZERO);
+
+        limiter.set_target_fps(Some(200.0));
+        assert_eq!(limiter.target_fps(), Some(200.0));
+        let start = Instant::now();
+        limiter.wait(true);
+        limiter.wait(true);
+        assert!(start.elapsed() >= 5 * MS);
+
+        // The background cap only applies without focus
+        limiter.set_background_fps(Some(1e-3));
+        let start = Instant::now();
+        limiter.wait(true);
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        for fps in [0.0, -60.0, f64::NAN, f64::INFINITY] {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                limiter.set_target_fps(Some(fps));
+            }));
+            assert!(result.is_err(), "fps {fps} was accepted");
+        }
+        assert_eq!(limiter.target_fps(), Some(200.0));
+        limiter.set_target_fps(None);
+        assert_eq!(limiter.target_fps(), None);
+    }
+
+    #[test]
+    fn frame_counter_dt_is_last_frame() {
+        let counter = counter_with_frames(&[10, 20, 30]);
+        assert_eq!(counter.dt(), 30 * MS);
+        assert_eq!(
+            counter.frame_times().collect::<Vec<_>>(),
+            [10 * MS, 20 * MS, 30 * MS]
+        );
+    }
+
+    #[test]
+    fn frame_counter_history_is_bounded() {
+        let mut counter = counter_with_frames(&[1, 2, 3, 4, 5]);
+        counter.set_history_len(3);
+        assert_eq!(
+            counter.frame_times().collect::<Vec<_>>(),
+            [3 * MS, 4 * MS, 5 * MS]
+        );
+    }
+
+    #[test]
+    fn frame_stats() {
+        // 1..=100ms, so percentiles land exactly on the values
+        let frame_times: Vec<u64> = (1..=100).collect();
+        let stats = counter_with_frames(&frame_times).stats();
+
+        assert_eq!(stats.frames, 100);
+        assert_eq!(stats.min, MS);
+        assert_eq!(stats.max, 100 * MS);
+        assert_eq!(stats.mean, Duration::from_micros(50_500));
+        assert_eq!(stats.p50, 50 * MS);
+        assert_eq!(stats.p95, 95 * MS);
+        assert_eq!(stats.p99, 99 * MS);
+        assert_relative_eq!(stats.low_1_percent_fps, 10.0);
+        assert_eq!(stats.jitter, MS);
+
+        assert_eq!(FrameCounter::default().stats(), FrameStats::default());
+    }
+
+    #[test]
+    fn frame_stats_jitter() {
+        let stats = counter_with_frames(&[10, 20, 10, 20, 10]).stats();
+        assert_eq!(stats.jitter, 10 * MS);
+        assert_eq!(stats.mean, 14 * MS);
+
+        let stats = counter_with_frames(&[16, 16, 16]).stats();
+        assert_eq!(stats.jitter, Duration::ZERO);
+    }
+
+    #[test]
+    fn frame_histogram() {
+        let histogram = counter_with_frames(&[1, 5, 9, 12, 40]).histogram(5 * MS, 3);
+        assert_eq!(histogram.counts, [1, 2, 2]);
     }
 }
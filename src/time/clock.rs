@@ -0,0 +1,203 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+// Monotonic time since some arbitrary start. Clocks are cheap handles, clones share their state so
+// e.g. every Ticker built from the engine's game clock follows its time scale. They're Send + Sync
+// so whatever holds one can still move to another thread.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct RealClock {
+    start: Instant,
+}
+
+impl Default for RealClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for RealClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// Only moves when told to, for tests
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, dt: Duration) {
+        *lock(&self.now) += dt;
+    }
+
+    // Going backwards isn't allowed, the time is clamped to the current one
+    pub fn set(&self, now: Duration) {
+        let mut current = lock(&self.now);
+        *current = current.max(now);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *lock(&self.now)
+    }
+}
+
+#[derive(Copy, Clone)]
+struct ScaledState {
+    scale: f64,
+    paused: bool,
+    // Scale changes only affect time from then on, so keep where both clocks were at the last one
+    source_base: Duration,
+    scaled_base: Duration,
+}
+
+impl ScaledState {
+    // Scaled time when the source clock reads source_now
+    fn at(&self, source_now: Duration) -> Duration {
+        if self.paused {
+            self.scaled_base
+        } else {
+            let elapsed = source_now.saturating_sub(self.source_base);
+            self.scaled_base + elapsed.mul_f64(self.scale)
+        }
+    }
+}
+
+// The state behind the locks stays valid even if a holder panicked, so poisoning is ignored
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Runs at a multiple of another clock's speed and can be paused
+#[derive(Clone)]
+pub struct ScaledClock {
+    source: Arc<dyn Clock>,
+    state: Arc<Mutex<ScaledState>>,
+}
+
+impl ScaledClock {
+    pub fn new<C: Clock + 'static>(source: C) -> Self {
+        let source_base = source.now();
+        Self {
+            source: Arc::new(source),
+            state: Arc::new(Mutex::new(ScaledState {
+                scale: 1.0,
+                paused: false,
+                source_base,
+                scaled_base: Duration::ZERO,
+            })),
+        }
+    }
+
+    pub fn scale(&self) -> f64 {
+        lock(&self.state).scale
+    }
+
+    // Negative scales are clamped to 0, this clock never runs backwards
+    pub fn set_scale(&self, scale: f64) {
+        self.rebase(|state| state.scale = scale.max(0.0));
+    }
+
+    pub fn is_paused(&self) -> bool {
+        lock(&self.state).paused
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.rebase(|state| state.paused = paused);
+    }
+
+    // How long dt of source time is on this clock right now
+    pub fn scale_duration(&self, dt: Duration) -> Duration {
+        let state = lock(&self.state);
+        if state.paused {
+            Duration::ZERO
+        } else {
+            dt.mul_f64(state.scale)
+        }
+    }
+
+    fn rebase(&self, f: impl FnOnce(&mut ScaledState)) {
+        let mut state = lock(&self.state);
+        let source_now = self.source.now();
+        state.scaled_base = state.at(source_now);
+        state.source_base = source_now;
+        f(&mut state);
+    }
+}
+
+impl Default for ScaledClock {
+    fn default() -> Self {
+        Self::new(RealClock::default())
+    }
+}
+
+impl Clock for ScaledClock {
+    fn now(&self) -> Duration {
+        lock(&self.state).at(self.source.now())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn manual_clones_share_time() {
+        let clock = ManualClock::new();
+        let other = clock.clone();
+
+        clock.advance(5 * MS);
+        assert_eq!(other.now(), 5 * MS);
+
+        other.set(3 * MS);
+        assert_eq!(clock.now(), 5 * MS);
+    }
+
+    fn assert_send<T: Send>() {}
+
+    #[test]
+    fn clocks_are_send() {
+        assert_send::<RealClock>();
+        assert_send::<ManualClock>();
+        assert_send::<ScaledClock>();
+    }
+
+    #[test]
+    fn scaled_follows_source() {
+        let source = ManualClock::new();
+        let scaled = ScaledClock::new(source.clone());
+
+        source.advance(10 * MS);
+        assert_eq!(scaled.now(), 10 * MS);
+
+        scaled.set_scale(0.5);
+        source.advance(10 * MS);
+        assert_eq!(scaled.now(), 15 * MS);
+
+        scaled.set_paused(true);
+        source.advance(10 * MS);
+        assert_eq!(scaled.now(), 15 * MS);
+        assert_eq!(scaled.scale_duration(10 * MS), Duration::ZERO);
+
+        scaled.set_paused(false);
+        scaled.set_scale(2.0);
+        source.advance(10 * MS);
+        assert_eq!(scaled.now(), 35 * MS);
+        assert_eq!(scaled.scale_duration(10 * MS), 20 * MS);
+    }
+}
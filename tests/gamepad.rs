@@ -0,0 +1,90 @@
+use baphomet::input::{GamepadAxis, GamepadButton};
+use baphomet::{Application, Engine};
+use sdl3::sys::gamepad::{
+    SDL_GAMEPAD_AXIS_COUNT, SDL_GAMEPAD_AXIS_LEFTX, SDL_GAMEPAD_AXIS_LEFTY,
+    SDL_GAMEPAD_BUTTON_COUNT, SDL_GAMEPAD_BUTTON_SOUTH,
+};
+use sdl3::sys::joystick::{
+    SDL_AttachVirtualJoystick, SDL_CloseJoystick, SDL_DetachVirtualJoystick,
+    SDL_JOYSTICK_TYPE_GAMEPAD, SDL_OpenJoystick, SDL_SetJoystickVirtualAxis,
+    SDL_SetJoystickVirtualButton, SDL_VirtualJoystickDesc,
+};
+
+#[derive(Default)]
+struct Pads {
+    connected: Vec<u32>,
+}
+
+impl Application for Pads {
+    fn update(&mut self, _engine: &mut Engine, _dt: f32) {}
+
+    fn draw(&mut self, _engine: &mut Engine, _alpha: f32) {}
+
+    fn gamepad_connected_event(&mut self, _engine: &mut Engine, id: u32) {
+        self.connected.push(id);
+    }
+}
+
+// Kept as a single test, tests in one binary run in parallel and SDL can't be initialized twice.
+// sdl3-rs doesn't wrap virtual joysticks, so this goes through the raw bindings.
+#[test]
+fn virtual_gamepad_updates_input_state() {
+    let mut engine = baphomet::init_headless(32, 32).expect("failed to init headless engine");
+    engine.set_gamepad_dead_zones(0.2, 0.1);
+    let mut app = Pads::default();
+
+    // A gamepad typed virtual joystick with every standard button and axis gets a default mapping
+    let mut desc: SDL_VirtualJoystickDesc = unsafe { std::mem::zeroed() };
+    desc.version = size_of::<SDL_VirtualJoystickDesc>() as u32;
+    desc.r#type = SDL_JOYSTICK_TYPE_GAMEPAD.0 as _;
+    desc.naxes = SDL_GAMEPAD_AXIS_COUNT.0 as _;
+    desc.nbuttons = SDL_GAMEPAD_BUTTON_COUNT.0 as _;
+    desc.axis_mask = (1 << SDL_GAMEPAD_AXIS_COUNT.0) - 1;
+    desc.button_mask = (1 << SDL_GAMEPAD_BUTTON_COUNT.0) - 1;
+    desc.name = c"baphomet test pad".as_ptr();
+
+    let virtual_id = unsafe { SDL_AttachVirtualJoystick(&desc) };
+    let joystick = unsafe { SDL_OpenJoystick(virtual_id) };
+    assert!(!joystick.is_null(), "failed to attach virtual joystick");
+
+    baphomet::run_frames(&mut engine, &mut app, 1);
+    assert_eq!(app.connected.len(), 1);
+    let id = app.connected[0];
+    let pad = |engine: &Engine| engine.input().gamepad(id).cloned().expect("pad is missing");
+
+    unsafe {
+        SDL_SetJoystickVirtualButton(joystick, SDL_GAMEPAD_BUTTON_SOUTH.0, true);
+        // 10% of the way is inside the stick dead zone
+        SDL_SetJoystickVirtualAxis(joystick, SDL_GAMEPAD_AXIS_LEFTX.0, i16::MAX / 10);
+        SDL_SetJoystickVirtualAxis(joystick, SDL_GAMEPAD_AXIS_LEFTY.0, i16::MAX);
+    }
+    baphomet::run_frames(&mut engine, &mut app, 1);
+    let state = pad(&engine);
+    assert!(state.is_button_down(GamepadButton::South));
+    assert!(state.was_pressed_this_frame(GamepadButton::South));
+    assert_eq!(state.axis(GamepadAxis::LeftX), 0.0);
+    assert_eq!(state.axis(GamepadAxis::LeftY), 1.0);
+
+    baphomet::run_frames(&mut engine, &mut app, 1);
+    let state = pad(&engine);
+    assert!(state.is_button_down(GamepadButton::South));
+    assert!(!state.was_pressed_this_frame(GamepadButton::South));
+
+    unsafe {
+        SDL_SetJoystickVirtualButton(joystick, SDL_GAMEPAD_BUTTON_SOUTH.0, false);
+        // 60% rescales to 50% with a 0.2 dead zone
+        SDL_SetJoystickVirtualAxis(joystick, SDL_GAMEPAD_AXIS_LEFTX.0, -(i16::MAX / 5 * 3));
+    }
+    baphomet::run_frames(&mut engine, &mut app, 1);
+    let state = pad(&engine);
+    assert!(!state.is_button_down(GamepadButton::South));
+    assert!(state.was_released_this_frame(GamepadButton::South));
+    assert!((state.axis(GamepadAxis::LeftX) + 0.5).abs() < 1e-3);
+
+    unsafe {
+        SDL_CloseJoystick(joystick);
+        SDL_DetachVirtualJoystick(virtual_id);
+    }
+    baphomet::run_frames(&mut engine, &mut app, 1);
+    assert!(engine.input().gamepad(id).is_none());
+}
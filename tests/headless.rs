@@ -0,0 +1,55 @@
+use baphomet::gfx::Image;
+use baphomet::{Application, Engine, Rgba};
+
+struct DrawShapes {
+    frames_left: usize,
+    screenshot: Option<Image>,
+}
+
+impl Application for DrawShapes {
+    fn update(&mut self, engine: &mut Engine, _dt: f32) {
+        if self.frames_left == 0 {
+            engine.shutdown();
+        }
+        self.frames_left = self.frames_left.saturating_sub(1);
+    }
+
+    fn draw(&mut self, engine: &mut Engine, _alpha: f32) {
+        let color = Rgba::hex(0xff0000ff);
+        engine.g2d.point((8.0, 8.0), &color);
+        engine.g2d.line((0.0, 0.0), (63.0, 63.0), &color);
+        engine.g2d.fill_tri(
+            (32.0, 8.0),
+            (56.0, 56.0),
+            (8.0, 56.0),
+            &color,
+            (32.0, 32.0),
+            0.0,
+        );
+
+        if self.frames_left == 0 {
+            self.screenshot = Some(engine.screenshot());
+        }
+    }
+}
+
+// Needs a GL 3.3 capable driver for SDL's offscreen video driver, Mesa's llvmpipe is enough
+#[test]
+fn headless_draws_shapes() {
+    let mut engine = baphomet::init_headless(64, 64).expect("failed to init headless engine");
+    assert!(engine.is_headless());
+
+    let mut app = DrawShapes {
+        frames_left: 3,
+        screenshot: None,
+    };
+    baphomet::run_app(&mut engine, &mut app);
+
+    assert_eq!(app.frames_left, 0);
+
+    let image = app.screenshot.expect("no screenshot was taken");
+    assert_eq!((image.width(), image.height()), (64, 64));
+    // The triangle points up, so these only match if the rows were flipped to top-left origin
+    assert_eq!(image.pixel(50, 50), Rgba::hex(0xff0000ff));
+    assert_eq!(image.pixel(50, 14), Rgba::hex(0x000000ff));
+}
@@ -0,0 +1,65 @@
+use baphomet::input::{MouseAction, MouseButton, Scancode};
+use baphomet::{Application, Engine, Event};
+
+#[derive(Default)]
+struct Counter {
+    jumps: usize,
+    clicks: Vec<(f32, f32, MouseButton)>,
+    text: String,
+}
+
+impl Application for Counter {
+    fn update(&mut self, engine: &mut Engine, _dt: f32) {
+        if engine.actions().pressed("jump") {
+            self.jumps += 1;
+        }
+    }
+
+    fn draw(&mut self, _engine: &mut Engine, _alpha: f32) {}
+
+    fn mouse_button_event(
+        &mut self,
+        _engine: &mut Engine,
+        x: f32,
+        y: f32,
+        button: MouseButton,
+        _clicks: u8,
+        action: MouseAction,
+    ) {
+        if action == MouseAction::Press {
+            self.clicks.push((x, y, button));
+        }
+    }
+
+    fn text_input_event(&mut self, _engine: &mut Engine, text: &str) {
+        self.text.push_str(text);
+    }
+}
+
+// Kept as a single test, tests in one binary run in parallel and SDL can't be initialized twice
+#[test]
+fn injected_events_reach_the_app() {
+    let mut engine = baphomet::init_headless(32, 32).expect("failed to init headless engine");
+    engine.action_map_mut().bind_str("jump", "Space").unwrap();
+
+    let mut app = Counter::default();
+    baphomet::run_frames(&mut engine, &mut app, 1);
+    assert_eq!(app.jumps, 0);
+
+    engine.inject_key_press(Scancode::Space);
+    engine.inject_mouse_click(10.0, 20.0, MouseButton::Left);
+    engine.inject_text("hi");
+
+    // Events are polled at the end of a frame, so the update that sees them is the one after
+    baphomet::run_frames(&mut engine, &mut app, 2);
+    assert_eq!(app.jumps, 1);
+    assert_eq!(app.clicks, vec![(10.0, 20.0, MouseButton::Left)]);
+    assert_eq!(app.text, "hi");
+    assert_eq!(engine.input().mouse_position(), (10.0, 20.0));
+    assert_eq!(engine.frame_index(), 3);
+
+    engine.inject(Event::Quit);
+    baphomet::run_frames(&mut engine, &mut app, 10);
+    assert!(!engine.is_running());
+    assert_eq!(engine.frame_index(), 4);
+}
@@ -0,0 +1,52 @@
+use baphomet::input::Scancode;
+use baphomet::{Application, Engine};
+use std::time::Duration;
+
+#[derive(Default)]
+struct Jumper {
+    dts: Vec<f32>,
+    jumps: Vec<u64>,
+}
+
+impl Application for Jumper {
+    fn update(&mut self, engine: &mut Engine, dt: f32) {
+        self.dts.push(dt);
+        if engine.actions().pressed("jump") {
+            self.jumps.push(engine.frame_index());
+        }
+    }
+
+    fn draw(&mut self, _engine: &mut Engine, _alpha: f32) {}
+}
+
+// Kept as a single test, tests in one binary run in parallel and SDL can't be initialized twice
+#[test]
+fn replay_keeps_the_recorded_dt_past_the_last_event() {
+    let mut engine = baphomet::init_headless(32, 32).expect("failed to init headless engine");
+    engine.action_map_mut().bind_str("jump", "Space").unwrap();
+    let dt = Duration::from_millis(20);
+
+    let mut app = Jumper::default();
+    baphomet::run_frames(&mut engine, &mut app, 1);
+
+    // The jump lands on the first recorded frame, the two after it are idle
+    engine.start_recording(dt);
+    engine.inject_key_press(Scancode::Space);
+    baphomet::run_frames(&mut engine, &mut app, 3);
+    let recording = engine.stop_recording().unwrap();
+    assert_eq!(recording.frame_count(), 3);
+    assert_eq!(recording.events().last().unwrap().0, 0);
+    assert!(app.dts[1..].iter().all(|v| *v == dt.as_secs_f32()));
+
+    let mut app = Jumper::default();
+    let replay_start = engine.frame_index();
+    engine.start_replay(recording);
+    baphomet::run_frames(&mut engine, &mut app, 3);
+    assert!(engine.is_replaying());
+
+    // Events are polled at the end of a frame, so one more update sees the last recorded frame
+    baphomet::run_frames(&mut engine, &mut app, 1);
+    assert!(!engine.is_replaying());
+    assert_eq!(app.dts, vec![dt.as_secs_f32(); 4]);
+    assert_eq!(app.jumps, vec![replay_start + 1]);
+}